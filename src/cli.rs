@@ -1,9 +1,37 @@
+use crate::config::Format;
+use crate::search::SearchPlatform;
+use crate::tree::TreeFormat;
 use clap::{Parser, Subcommand};
 use libium::config::structs::ModLoader;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Parser)]
 #[clap(version, about = "Mod manager for Minecraft servers")]
 pub struct Ferrite {
+    #[clap(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Environment from the config's `environments` to use, e.g. staging"
+    )]
+    pub env: Option<String>,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Config file to use instead of searching for ferrite.yaml"
+    )]
+    pub config: Option<PathBuf>,
+
+    #[clap(
+        short = 'C',
+        global = true,
+        value_name = "DIR",
+        help = "Run as if ferrite was started in DIR"
+    )]
+    pub directory: Option<PathBuf>,
+
     #[clap(subcommand)]
     pub subcommand: SubCommands,
 }
@@ -32,25 +60,109 @@ pub enum SubCommands {
     #[clap(about = "Start the Minecraft server")]
     Start,
 
-    #[clap(visible_aliases = ["i", "install"], about = "Add mods by identifier")]
+    #[clap(visible_alias = "i", about = "Add mods by identifier")]
     Add {
         #[clap(
             required = true,
             value_name = "IDENTIFIER",
-            help = "Mod identifier(s): Modrinth slug, CurseForge project ID, or GitHub 'owner/repo'"
+            help = "Mod identifier(s): Modrinth slug, CurseForge project ID, or GitHub 'owner/repo', with @VERSION to pin, e.g. sodium@mc1.21-0.6.0"
         )]
         identifiers: Vec<String>,
+
+        #[clap(
+            long = "tag",
+            value_name = "TAG",
+            help = "Tag the added mods, can be repeated"
+        )]
+        tags: Vec<String>,
     },
 
-    #[clap(visible_alias = "rm", about = "Remove mods by name")]
+    #[clap(about = "Search Modrinth and CurseForge for mods to add")]
+    Search {
+        #[clap(required = true, value_name = "QUERY", help = "Words to search for")]
+        query: Vec<String>,
+
+        #[clap(long, value_enum, help = "Only search this platform")]
+        platform: Option<SearchPlatform>,
+
+        #[clap(
+            long,
+            help = "Print the results as JSON instead of selecting mods to add"
+        )]
+        json: bool,
+    },
+
+    #[clap(visible_alias = "rm", about = "Remove mods by name or tag")]
     Remove {
-        #[clap(required = true, value_name = "NAME", help = "Mod name(s) to remove")]
+        #[clap(
+            required_unless_present = "tag",
+            value_name = "NAME",
+            help = "Mod name(s) to remove"
+        )]
         mod_names: Vec<String>,
+
+        #[clap(long, help = "Remove every mod with this tag")]
+        tag: Option<String>,
     },
 
-    #[clap(about = "Disable mods by name (moves to disabled list)")]
+    #[clap(about = "Disable mods by name or tag (moves to disabled list)")]
     Disable {
-        #[clap(required = true, value_name = "NAME", help = "Mod name(s) to disable")]
+        #[clap(
+            required_unless_present = "tag",
+            value_name = "NAME",
+            help = "Mod name(s) to disable"
+        )]
+        mod_names: Vec<String>,
+
+        #[clap(long, help = "Disable every mod with this tag")]
+        tag: Option<String>,
+    },
+
+    #[clap(about = "Enable disabled mods by name or tag (moves back to the mods list)")]
+    Enable {
+        #[clap(value_name = "NAME", help = "Mod name(s) to enable")]
+        mod_names: Vec<String>,
+
+        #[clap(long, help = "Enable every disabled mod with this tag")]
+        tag: Option<String>,
+    },
+
+    #[clap(about = "Pin a mod to a version so that upgrades keep it")]
+    Pin {
+        #[clap(value_name = "NAME", help = "Mod to pin")]
+        mod_name: String,
+
+        #[clap(
+            value_name = "VERSION",
+            help = "Version number or ID, picked from the compatible versions if omitted"
+        )]
+        version: Option<String>,
+    },
+
+    #[clap(about = "Let pinned mods follow the latest compatible version again")]
+    Unpin {
+        #[clap(required = true, value_name = "NAME", help = "Mod name(s) to unpin")]
+        mod_names: Vec<String>,
+    },
+
+    #[clap(about = "Tag mods, so they can be listed, disabled and enabled together")]
+    Tag {
+        #[clap(value_name = "TAG", help = "Tag to give the mods")]
+        tag: String,
+
+        #[clap(required = true, value_name = "NAME", help = "Mod name(s) to tag")]
+        mod_names: Vec<String>,
+    },
+
+    #[clap(about = "Remove a tag from mods")]
+    Untag {
+        #[clap(value_name = "TAG", help = "Tag to remove")]
+        tag: String,
+
+        #[clap(
+            value_name = "NAME",
+            help = "Mod name(s) to untag, every mod with the tag if omitted"
+        )]
         mod_names: Vec<String>,
     },
 
@@ -71,8 +183,17 @@ pub enum SubCommands {
         script: String,
     },
 
+    #[clap(about = "Show a mod's project details, installed file and latest version")]
+    Info {
+        #[clap(value_name = "NAME", help = "Name, ID or slug of the mod")]
+        mod_name: String,
+    },
+
     #[clap(visible_alias = "ls", about = "List all installed mods")]
-    List,
+    List {
+        #[clap(long, help = "Only list mods with this tag, disabled ones included")]
+        tag: Option<String>,
+    },
 
     #[clap(
         visible_alias = "update",
@@ -80,6 +201,87 @@ pub enum SubCommands {
     )]
     Upgrade,
 
+    #[clap(about = "List the mods with newer compatible versions, without downloading them")]
+    Outdated,
+
+    #[clap(about = "Remove dependencies no mod needs anymore and jars not in ferrite.lock")]
+    Prune,
+
+    #[clap(about = "Install the mod files recorded in ferrite.lock")]
+    Install {
+        #[clap(
+            long,
+            help = "Fail instead of resolving when ferrite.lock is missing or out of date"
+        )]
+        locked: bool,
+
+        /// `install` used to be an alias of `add`, so `install <IDENTIFIER>` still adds mods
+        #[clap(hide = true, conflicts_with = "locked")]
+        identifiers: Vec<String>,
+    },
+
+    #[clap(about = "Compare the config with another config or with the installed mods")]
+    Diff {
+        #[clap(
+            value_name = "CONFIG",
+            required_unless_present = "installed",
+            conflicts_with = "installed",
+            help = "Config to compare with, e.g. ../creative/ferrite.yaml"
+        )]
+        other: Option<PathBuf>,
+
+        #[clap(
+            long,
+            help = "Compare the mods and their dependencies with the jars in output_path"
+        )]
+        installed: bool,
+
+        #[clap(long, help = "Print the differences as JSON")]
+        json: bool,
+    },
+
+    #[clap(about = "Show which mod pulled in which dependency")]
+    Tree {
+        #[clap(
+            long,
+            value_enum,
+            default_value_t = TreeFormat::Text,
+            help = "Print the graph as text, Graphviz DOT or JSON"
+        )]
+        format: TreeFormat,
+    },
+
+    #[clap(about = "Explain why a jar or dependency is installed")]
+    Why {
+        #[clap(
+            value_name = "JAR_OR_NAME",
+            help = "Jar file name or path, or the name or ID of a locked mod"
+        )]
+        target: String,
+    },
+
+    #[clap(about = "List the changes made to the configuration")]
+    History {
+        #[clap(long, help = "Show the config diff of every change")]
+        diff: bool,
+    },
+
+    #[clap(about = "Undo the last changes to the configuration and mod files")]
+    Undo {
+        #[clap(
+            default_value_t = 1,
+            value_name = "N",
+            help = "Number of changes to undo"
+        )]
+        count: usize,
+    },
+
+    #[clap(about = "Inspect and check the configuration")]
+    Config {
+        #[clap(subcommand)]
+        subcommand: ConfigCommands,
+    },
+
     #[clap(about = "Upgrade Minecraft version and update mods")]
     UpdateVersion {
         #[clap(
@@ -91,3 +293,48 @@ pub enum SubCommands {
         version: Option<String>,
     },
 }
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ConfigCommands {
+    #[clap(about = "Print the JSON Schema of ferrite.yaml")]
+    Schema,
+
+    #[clap(about = "Check ferrite.yaml for mistakes")]
+    Validate,
+
+    #[clap(about = "Upgrade ferrite.yaml to the latest config version")]
+    Migrate {
+        #[clap(long, help = "Print the changes as a diff instead of writing them")]
+        dry_run: bool,
+    },
+
+    #[clap(about = "Print the value of a config key, e.g. ferium.game_versions")]
+    Get {
+        #[clap(value_name = "KEY", help = "Dotted path of the key")]
+        key: String,
+    },
+
+    #[clap(about = "Set a config key, e.g. server.wrapper \"java -Xmx8G -jar {} nogui\"")]
+    Set {
+        #[clap(value_name = "KEY", help = "Dotted path of the key")]
+        key: String,
+
+        #[clap(
+            value_name = "VALUE",
+            help = "New value, read as YAML, e.g. true or [1.21.1, 1.21.2]"
+        )]
+        value: String,
+    },
+
+    #[clap(about = "Remove an optional config key or map entry")]
+    Unset {
+        #[clap(value_name = "KEY", help = "Dotted path of the key")]
+        key: String,
+    },
+
+    #[clap(about = "Convert the config to another format")]
+    Convert {
+        #[clap(long, value_enum, value_name = "FORMAT", help = "Format to convert to")]
+        to: Format,
+    },
+}
@@ -0,0 +1,752 @@
+use super::patch::{lookup_mut, lookup_or_insert};
+use serde_norway::Value;
+
+/// Values that make a key hold an empty mapping or sequence, which entries replace
+const EMPTY_VALUES: [&str; 5] = ["", "{}", "[]", "~", "null"];
+
+/// A config file, edited in place so that comments and formatting are kept
+///
+/// Every edit is applied to both the parsed value and the lines of the file. Only block style
+/// YAML is edited as text, if an edit touches anything else (such as a flow mapping) the text
+/// is dropped and the file is serialized from the value instead.
+#[derive(Debug, Clone)]
+pub struct Document {
+    value: Value,
+    lines: Option<Vec<String>>,
+    /// How much further than their key block sequences are indented
+    sequence_indent: usize,
+}
+
+/// A `key: value` line and the lines of its value
+struct Entry {
+    line: usize,
+    indent: usize,
+    /// The byte after the key's colon
+    column: usize,
+    end: usize,
+}
+
+/// The lines holding the entries of a block mapping
+struct Block {
+    /// The line of the key holding the mapping, `None` for the root
+    key: Option<usize>,
+    start: usize,
+    end: usize,
+    indent: usize,
+    empty: bool,
+}
+
+/// The lines holding the items of a block sequence
+struct Sequence {
+    key: usize,
+    items: Vec<(usize, usize)>,
+    end: usize,
+    indent: usize,
+}
+
+impl Document {
+    pub fn new(text: &str, value: Value) -> Self {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let sequence_indent = detect_sequence_indent(&lines);
+        Self {
+            value,
+            lines: Some(lines),
+            sequence_indent,
+        }
+    }
+
+    /// A document without text to edit, which is always serialized from its value
+    pub fn from_value(value: Value) -> Self {
+        Self {
+            value,
+            lines: None,
+            sequence_indent: 2,
+        }
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// The edited file, `None` if it can no longer be edited in place
+    pub fn text(&self) -> Option<String> {
+        let text = self.lines.as_ref()?.join("\n") + "\n";
+        let parsed: Value = serde_norway::from_str(&text).ok()?;
+        (parsed == self.value).then_some(text)
+    }
+
+    /// Set the value at `path`, creating mappings along the way if needed
+    pub fn set(&mut self, path: &[String], value: Value) {
+        self.edit(|lines| lines.set(path, &value));
+        *lookup_or_insert(&mut self.value, path) = value;
+    }
+
+    /// Remove the key at `path`
+    pub fn remove(&mut self, path: &[String]) {
+        if let Some((key, parent)) = path.split_last()
+            && let Some(Value::Mapping(map)) = lookup_mut(&mut self.value, parent)
+            && map.shift_remove(key.as_str()).is_some()
+        {
+            self.edit(|lines| lines.remove(path));
+        }
+    }
+
+    /// Append `item` to the sequence at `path`
+    pub fn push(&mut self, path: &[String], item: Value) {
+        self.edit(|lines| lines.push(path, &item));
+        match lookup_or_insert(&mut self.value, path) {
+            Value::Sequence(items) => items.push(item),
+            list => *list = Value::Sequence(vec![item]),
+        }
+    }
+
+    /// Remove the item at `index` from the sequence at `path`
+    pub fn remove_item(&mut self, path: &[String], index: usize) {
+        if let Some(Value::Sequence(items)) = lookup_mut(&mut self.value, path)
+            && index < items.len()
+        {
+            items.remove(index);
+            self.edit(|lines| lines.remove_item(path, index));
+        }
+    }
+
+    /// Replace the item at `index` in the sequence at `path`
+    pub fn replace_item(&mut self, path: &[String], index: usize, item: Value) {
+        if let Some(Value::Sequence(items)) = lookup_mut(&mut self.value, path)
+            && index < items.len()
+        {
+            items[index] = item.clone();
+            self.edit(|lines| lines.replace_item(path, index, &item));
+        }
+    }
+
+    /// Apply `edit` to the lines, dropping them if it could not be applied
+    fn edit(&mut self, edit: impl FnOnce(&mut Lines) -> Option<()>) {
+        if let Some(lines) = self.lines.take() {
+            let mut lines = Lines {
+                lines,
+                sequence_indent: self.sequence_indent,
+            };
+            if edit(&mut lines).is_some() {
+                self.lines = Some(lines.lines);
+            }
+        }
+    }
+}
+
+struct Lines {
+    lines: Vec<String>,
+    sequence_indent: usize,
+}
+
+impl Lines {
+    fn set(&mut self, path: &[String], value: &Value) -> Option<()> {
+        let (key, parents) = path.split_last()?;
+
+        // Find the deepest mapping that exists, and create the rest of the path in it
+        let mut depth = parents.len();
+        let block = loop {
+            match self.block(&parents[..depth])? {
+                Some(block) => break block,
+                None => depth -= 1,
+            }
+        };
+        if depth < parents.len() {
+            let nested = path[depth + 1..]
+                .iter()
+                .rev()
+                .fold(value.clone(), |value, key| {
+                    Value::Mapping([(Value::String(key.clone()), value)].into_iter().collect())
+                });
+            return self.insert_entry(&block, &parents[depth], &nested);
+        }
+
+        match self.find(&block, key) {
+            Some(entry) => self.replace_entry(&entry, value),
+            None => self.insert_entry(&block, key, value),
+        }
+    }
+
+    fn remove(&mut self, path: &[String]) -> Option<()> {
+        let (key, parents) = path.split_last()?;
+        let block = self.block(parents)??;
+        let entry = self.find(&block, key)?;
+
+        let others = (block.start..block.end)
+            .filter(|i| !(entry.line..entry.end).contains(i))
+            .any(|i| is_content(&self.lines[i]));
+        self.lines.drain(entry.line..entry.end);
+        if !others && let Some(key) = block.key {
+            self.set_inline(key, "{}");
+        }
+
+        Some(())
+    }
+
+    fn push(&mut self, path: &[String], item: &Value) -> Option<()> {
+        let Some(sequence) = self.sequence(path)? else {
+            return self.set(path, &Value::Sequence(vec![item.clone()]));
+        };
+
+        if sequence.items.is_empty() {
+            self.set_inline(sequence.key, "");
+        }
+        let rendered = self.item_lines(item, sequence.indent);
+        self.lines.splice(sequence.end..sequence.end, rendered);
+
+        Some(())
+    }
+
+    fn remove_item(&mut self, path: &[String], index: usize) -> Option<()> {
+        let sequence = self.sequence(path)??;
+        let (start, end) = *sequence.items.get(index)?;
+
+        self.lines.drain(start..end);
+        if sequence.items.len() == 1 {
+            self.set_inline(sequence.key, "[]");
+        }
+
+        Some(())
+    }
+
+    fn replace_item(&mut self, path: &[String], index: usize, item: &Value) -> Option<()> {
+        let sequence = self.sequence(path)??;
+        let (start, end) = *sequence.items.get(index)?;
+
+        let rendered = self.item_lines(item, sequence.indent);
+        self.lines.splice(start..end, rendered);
+
+        Some(())
+    }
+
+    /// The mapping at `path`, `None` if it is not a block mapping, `Some(None)` if it is missing
+    fn block(&self, path: &[String]) -> Option<Option<Block>> {
+        let content = self.content_lines(0, self.lines.len());
+        let mut block = Block {
+            key: None,
+            start: 0,
+            end: content.clone().last().map_or(self.lines.len(), |i| i + 1),
+            indent: content.clone().next().map_or(0, |i| indent(&self.lines[i])),
+            empty: content.clone().next().is_none(),
+        };
+
+        for key in path {
+            let Some(entry) = self.find(&block, key) else {
+                return Some(None);
+            };
+            let first = self.content_lines(entry.line + 1, entry.end).next();
+            if first.is_some_and(|i| is_item(&self.lines[i])) || !self.is_empty_inline(&entry) {
+                return None;
+            }
+            block = Block {
+                key: Some(entry.line),
+                start: entry.line + 1,
+                end: entry.end,
+                indent: first.map_or(entry.indent + 2, |i| indent(&self.lines[i])),
+                empty: first.is_none(),
+            };
+        }
+
+        Some(Some(block))
+    }
+
+    /// The sequence at `path`, `None` if it is not a block sequence, `Some(None)` if it is missing
+    fn sequence(&self, path: &[String]) -> Option<Option<Sequence>> {
+        let (key, parents) = path.split_last()?;
+        let Some(block) = self.block(parents)? else {
+            return Some(None);
+        };
+        let Some(entry) = self.find(&block, key) else {
+            return Some(None);
+        };
+        if !self.is_empty_inline(&entry) {
+            return None;
+        }
+
+        let first = self.content_lines(entry.line + 1, entry.end).next();
+        let indent = match first {
+            Some(i) if is_item(&self.lines[i]) => indent(&self.lines[i]),
+            Some(_) => return None,
+            None => entry.indent + self.sequence_indent,
+        };
+
+        let items = self
+            .content_lines(entry.line + 1, entry.end)
+            .filter(|&i| is_item(&self.lines[i]) && self::indent(&self.lines[i]) == indent)
+            .map(|i| (i, self.end_of(i, |line| self::indent(line) <= indent)))
+            .collect();
+
+        Some(Some(Sequence {
+            key: entry.line,
+            items,
+            end: entry.end,
+            indent,
+        }))
+    }
+
+    /// The entry for `key` in `block`
+    fn find(&self, block: &Block, key: &str) -> Option<Entry> {
+        if block.empty {
+            return None;
+        }
+
+        self.content_lines(block.start, block.end)
+            .filter(|&i| indent(&self.lines[i]) == block.indent)
+            .find_map(|i| {
+                let (found, column) = parse_key(&self.lines[i])?;
+                (found == key).then(|| Entry {
+                    line: i,
+                    indent: block.indent,
+                    column,
+                    end: self.end_of(i, |line| {
+                        indent(line) < block.indent
+                            || (indent(line) == block.indent && !is_item(line))
+                    }),
+                })
+            })
+    }
+
+    /// The line after the last content line that belongs to the node starting at `start`
+    ///
+    /// The node ends at the first content line that `ends` matches. Comments and blank lines
+    /// after it are left to whatever follows.
+    fn end_of(&self, start: usize, ends: impl Fn(&str) -> bool) -> usize {
+        self.content_lines(start + 1, self.lines.len())
+            .take_while(|&i| !ends(&self.lines[i]))
+            .last()
+            .map_or(start + 1, |i| i + 1)
+    }
+
+    fn content_lines(&self, start: usize, end: usize) -> impl Iterator<Item = usize> + Clone {
+        (start..end).filter(|&i| is_content(&self.lines[i]))
+    }
+
+    fn is_empty_inline(&self, entry: &Entry) -> bool {
+        let (value, _) = split_comment(&self.lines[entry.line][entry.column..]);
+        EMPTY_VALUES.contains(&value.trim())
+    }
+
+    /// Replace the inline value of the key on `line` with `value`, keeping its comment
+    fn set_inline(&mut self, line: usize, value: &str) {
+        let Some((_, column)) = parse_key(&self.lines[line]) else {
+            return;
+        };
+        let text = &self.lines[line];
+        let (_, comment) = split_comment(&text[column..]);
+        let separator = if value.is_empty() { "" } else { " " };
+        self.lines[line] = format!("{}{separator}{value}{comment}", &text[..column]);
+    }
+
+    fn insert_entry(&mut self, block: &Block, key: &str, value: &Value) -> Option<()> {
+        if block.empty
+            && let Some(line) = block.key
+        {
+            self.set_inline(line, "");
+        }
+
+        let prefix = format!(
+            "{}{}:",
+            " ".repeat(block.indent),
+            inline(&Value::String(key.to_string()))?.join("")
+        );
+        let rendered = self.entry_lines(prefix, "", value, block.indent, self.sequence_indent)?;
+        self.lines.splice(block.end..block.end, rendered);
+
+        Some(())
+    }
+
+    fn replace_entry(&mut self, entry: &Entry, value: &Value) -> Option<()> {
+        let text = &self.lines[entry.line];
+        let prefix = text[..entry.column].to_string();
+        let (_, comment) = split_comment(&text[entry.column..]);
+        let comment = comment.to_string();
+
+        // Keep the indent of a sequence that is replaced by another
+        let sequence_indent = self
+            .content_lines(entry.line + 1, entry.end)
+            .next()
+            .filter(|&i| is_item(&self.lines[i]))
+            .map_or(self.sequence_indent, |i| {
+                indent(&self.lines[i]).saturating_sub(entry.indent)
+            });
+
+        let rendered = self.entry_lines(prefix, &comment, value, entry.indent, sequence_indent)?;
+        self.lines.splice(entry.line..entry.end, rendered);
+
+        Some(())
+    }
+
+    /// Render `value` after `prefix`, which ends with the key and its colon
+    fn entry_lines(
+        &self,
+        prefix: String,
+        comment: &str,
+        value: &Value,
+        indent: usize,
+        sequence_indent: usize,
+    ) -> Option<Vec<String>> {
+        if let Some(lines) = inline(value) {
+            let (first, rest) = lines.split_first()?;
+            return Some(
+                std::iter::once(format!("{prefix} {first}{comment}"))
+                    .chain(
+                        rest.iter()
+                            .map(|line| format!("{}{line}", " ".repeat(indent))),
+                    )
+                    .collect(),
+            );
+        }
+
+        let (tag, value) = match value {
+            Value::Tagged(tagged) => (format!(" {}", tagged.tag), &tagged.value),
+            value => (String::new(), value),
+        };
+        let child_indent = match value {
+            Value::Sequence(_) => indent + sequence_indent,
+            _ => indent + 2,
+        };
+
+        let mut lines = vec![format!("{prefix}{tag}{comment}")];
+        lines.extend(self.block_lines(value, child_indent)?);
+        Some(lines)
+    }
+
+    /// Render a non-empty mapping or sequence as a block at `indent`
+    fn block_lines(&self, value: &Value, indent: usize) -> Option<Vec<String>> {
+        let mut lines = Vec::new();
+        match value {
+            Value::Mapping(map) => {
+                for (key, value) in map {
+                    let prefix = format!("{}{}:", " ".repeat(indent), inline(key)?.join(""));
+                    lines.extend(self.entry_lines(
+                        prefix,
+                        "",
+                        value,
+                        indent,
+                        self.sequence_indent,
+                    )?);
+                }
+            }
+            Value::Sequence(items) => {
+                for item in items {
+                    lines.extend(self.item_lines(item, indent));
+                }
+            }
+            _ => return None,
+        }
+        Some(lines)
+    }
+
+    /// Render `item` as a sequence item at `indent`
+    fn item_lines(&self, item: &Value, indent: usize) -> Vec<String> {
+        let pad = " ".repeat(indent);
+
+        if let Some(lines) = inline(item) {
+            return lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| match i {
+                    0 => format!("{pad}- {line}"),
+                    _ => format!("{pad}{line}"),
+                })
+                .collect();
+        }
+
+        let mut lines = match item {
+            Value::Tagged(tagged) => {
+                let mut lines = vec![format!("{pad}- {}", tagged.tag)];
+                lines.extend(
+                    self.block_lines(&tagged.value, indent + 2)
+                        .unwrap_or_default(),
+                );
+                return lines;
+            }
+            item => self.block_lines(item, indent + 2).unwrap_or_default(),
+        };
+        if let Some(first) = lines.first_mut() {
+            *first = format!("{pad}- {}", &first[indent + 2..]);
+        }
+        lines
+    }
+}
+
+/// Render a scalar, empty collection or tagged scalar on a single line
+///
+/// Multi-line strings are rendered as block scalars, with their content on the following lines
+fn inline(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::Mapping(map) if map.is_empty() => Some(vec!["{}".to_string()]),
+        Value::Sequence(items) if items.is_empty() => Some(vec!["[]".to_string()]),
+        Value::Mapping(_) | Value::Sequence(_) => None,
+        Value::Tagged(tagged) => {
+            let mut lines = inline(&tagged.value)?;
+            lines[0] = format!("{} {}", tagged.tag, lines[0]);
+            Some(lines)
+        }
+        _ => Some(
+            serde_norway::to_string(value)
+                .ok()?
+                .lines()
+                .map(str::to_string)
+                .collect(),
+        ),
+    }
+}
+
+/// How much further than their key the block sequences in `lines` are indented
+fn detect_sequence_indent(lines: &[String]) -> usize {
+    let content: Vec<&String> = lines.iter().filter(|line| is_content(line)).collect();
+    content
+        .windows(2)
+        .find(|pair| {
+            !is_item(pair[0])
+                && is_item(pair[1])
+                && parse_key(pair[0]).is_some_and(|(_, column)| {
+                    EMPTY_VALUES.contains(&split_comment(&pair[0][column..]).0.trim())
+                })
+        })
+        .map_or(2, |pair| indent(pair[1]).saturating_sub(indent(pair[0])))
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_content(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#') && line != "---"
+}
+
+fn is_item(line: &str) -> bool {
+    let line = line.trim_start();
+    line == "-" || line.starts_with("- ")
+}
+
+/// The key of a `key: value` line and the byte after its colon
+fn parse_key(line: &str) -> Option<(String, usize)> {
+    let start = indent(line);
+    let rest = &line[start..];
+
+    let end = match rest.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let mut escaped = false;
+            let close = rest.char_indices().skip(1).find(|&(_, c)| {
+                let close = c == quote && !escaped;
+                escaped = quote == '"' && c == '\\' && !escaped;
+                close
+            })?;
+            close.0 + 1
+        }
+        '-' | '#' | '?' | '[' | '{' | '!' | '&' | '*' | '|' | '>' => return None,
+        _ => {
+            rest.char_indices()
+                .find(|&(i, c)| c == ':' && rest[i + 1..].chars().next().is_none_or(|c| c == ' '))?
+                .0
+        }
+    };
+
+    let colon = end + rest[end..].find(':')?;
+    if !rest[end..colon].trim().is_empty() {
+        return None;
+    }
+
+    let key = match rest.chars().next()? {
+        '"' | '\'' => serde_norway::from_str(&rest[..end]).ok()?,
+        _ => rest[..end].trim_end().to_string(),
+    };
+    Some((key, start + colon + 1))
+}
+
+/// Split the rest of a line after a colon into its value and its comment
+fn split_comment(rest: &str) -> (&str, &str) {
+    let mut quote = None;
+    let mut previous = ' ';
+
+    for (i, c) in rest.char_indices() {
+        match quote {
+            Some('"') if c == '\\' && previous == '\\' => {
+                previous = ' ';
+                continue;
+            }
+            Some(q) if c == q && !(q == '"' && previous == '\\') => quote = None,
+            Some(_) => {}
+            None if (c == '"' || c == '\'') && " [{,".contains(previous) => quote = Some(c),
+            None if c == '#' && (previous == ' ' || previous == '\t') => {
+                let value = rest[..i].trim_end();
+                return (value, &rest[value.len()..]);
+            }
+            None => {}
+        }
+        previous = c;
+    }
+
+    (rest, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Document;
+    use serde_norway::Value;
+
+    fn parse(text: &str) -> Document {
+        Document::new(text, serde_norway::from_str(text).unwrap())
+    }
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    fn yaml(value: &str) -> Value {
+        serde_norway::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn keeps_comments() {
+        let mut document = parse(
+            "# Server mods\nautoupdate: true # upgrade after changes\n\n# Where jars go\noutput_path: mods\n",
+        );
+        document.set(&path("autoupdate"), yaml("false"));
+        document.set(&path("output_path"), yaml("plugins"));
+
+        assert_eq!(
+            document.text().unwrap(),
+            "# Server mods\nautoupdate: false # upgrade after changes\n\n# Where jars go\noutput_path: plugins\n"
+        );
+    }
+
+    #[test]
+    fn creates_nested_keys() {
+        let mut document = parse("version: 4\n");
+        document.set(&path("git.commands.upgrade"), yaml("Off"));
+
+        assert_eq!(
+            document.text().unwrap(),
+            "version: 4\ngit:\n  commands:\n    upgrade: Off\n"
+        );
+    }
+
+    #[test]
+    fn fills_empty_collections() {
+        let mut document = parse("ferium:\n  overrides: {}\n  mods: [] # none yet\n");
+        document.set(
+            &path("ferium.overrides.P7dR8mSH"),
+            yaml("!ModrinthProject AANobbMI"),
+        );
+        document.push(&path("ferium.mods"), yaml("name: Sodium\nslug: sodium"));
+
+        assert_eq!(
+            document.text().unwrap(),
+            "ferium:\n  overrides:\n    P7dR8mSH: !ModrinthProject AANobbMI\n  mods: # none yet\n    - name: Sodium\n      slug: sodium\n"
+        );
+    }
+
+    #[test]
+    fn empties_collections() {
+        let mut document = parse(
+            "ferium:\n  overrides:\n    P7dR8mSH: !ModrinthProject AANobbMI\n  mods:\n    - name: Sodium\n",
+        );
+        document.remove(&path("ferium.overrides.P7dR8mSH"));
+        document.remove_item(&path("ferium.mods"), 0);
+
+        assert_eq!(
+            document.text().unwrap(),
+            "ferium:\n  overrides: {}\n  mods: []\n"
+        );
+    }
+
+    #[test]
+    fn writes_tags() {
+        let mut document = parse(
+            "key_store: Pass\nferium:\n  mods:\n    - name: Lithium\n      identifier: !ModrinthProject gvQqBUqZ\n",
+        );
+        document.set(&path("key_store"), yaml("!Command op read {}"));
+        document.push(
+            &path("ferium.mods"),
+            yaml("name: fabric-carpet\nidentifier: !GitHubRepository [gnembon, fabric-carpet]"),
+        );
+
+        assert_eq!(
+            document.text().unwrap(),
+            "key_store: !Command op read {}\nferium:\n  mods:\n    - name: Lithium\n      identifier: !ModrinthProject gvQqBUqZ\n    - name: fabric-carpet\n      identifier: !GitHubRepository\n        - gnembon\n        - fabric-carpet\n"
+        );
+    }
+
+    #[test]
+    fn keeps_sequence_indent() {
+        let mut document = parse("game_versions:\n- 1.21.1\nmod_loaders:\n- Fabric\n");
+        document.push(&path("game_versions"), yaml("1.21.4"));
+        document.set(&path("mod_loaders"), yaml("[Fabric, Quilt]"));
+
+        assert_eq!(
+            document.text().unwrap(),
+            "game_versions:\n- 1.21.1\n- 1.21.4\nmod_loaders:\n- Fabric\n- Quilt\n"
+        );
+    }
+
+    #[test]
+    fn replaces_items() {
+        let mut document =
+            parse("mods:\n  - name: Sodium # fast\n    slug: sodium\n  - name: Lithium\n");
+        document.replace_item(
+            &path("mods"),
+            0,
+            yaml("name: Sodium\nslug: sodium\nside: Both"),
+        );
+
+        assert_eq!(
+            document.text().unwrap(),
+            "mods:\n  - name: Sodium\n    slug: sodium\n    side: Both\n  - name: Lithium\n"
+        );
+    }
+
+    #[test]
+    fn flow_collections_are_not_edited_in_place() {
+        let mut document =
+            parse("server: {wrapper: java, executable: server.jar}\nversions: [1.21.1]\n");
+        document.set(&path("server.wrapper"), yaml("java -Xmx8G -jar server.jar"));
+        assert!(document.text().is_none());
+        assert_eq!(
+            document.value(),
+            &yaml(
+                "server: {wrapper: java -Xmx8G -jar server.jar, executable: server.jar}\nversions: [1.21.1]"
+            )
+        );
+
+        let mut document = parse("versions: [1.21.1] # supported\n");
+        document.push(&path("versions"), yaml("1.21.4"));
+        assert!(document.text().is_none());
+        assert_eq!(document.value(), &yaml("versions: [1.21.1, 1.21.4]"));
+    }
+
+    #[test]
+    fn replaces_flow_collections() {
+        let mut document = parse("versions: [1.21.1] # supported\n");
+        document.set(&path("versions"), yaml("[1.21.4]"));
+
+        assert_eq!(
+            document.text().unwrap(),
+            "versions: # supported\n  - 1.21.4\n"
+        );
+    }
+
+    #[test]
+    fn keeps_anchors() {
+        let mut document = parse(
+            "defaults: &defaults\n  side: Both\nserver:\n  <<: *defaults\nautoupdate: true\n",
+        );
+        document.set(&path("autoupdate"), yaml("false"));
+
+        assert_eq!(
+            document.text().unwrap(),
+            "defaults: &defaults\n  side: Both\nserver:\n  <<: *defaults\nautoupdate: false\n"
+        );
+    }
+
+    #[test]
+    fn anchored_values_are_not_edited_in_place() {
+        let mut document = parse("defaults: &defaults\n  side: Both\nautoupdate: true\n");
+        document.set(&path("defaults.side"), yaml("Server"));
+
+        assert!(document.text().is_none());
+    }
+}
@@ -0,0 +1,139 @@
+use super::error::{ConfigError, Result};
+use super::patch::{lookup, lookup_mut, lookup_or_insert};
+use super::source::to_value;
+use super::types::FerriteConfig;
+use serde_norway::Value;
+use std::mem;
+
+/// Split a dotted path such as `server.wrapper` into its keys
+fn parse_path(path: &str) -> Result<Vec<String>> {
+    let keys: Vec<String> = path.split('.').map(str::to_string).collect();
+    if keys.iter().any(String::is_empty) {
+        return Err(ConfigError::UnknownKey(path.to_string()));
+    }
+    Ok(keys)
+}
+
+/// The value at the dotted `path` in `document`, a config as it is written
+///
+/// `${VAR}` references are returned as they are, so secrets are never printed. Keys that are
+/// not written fall back to their default.
+pub fn get(document: &Value, path: &str) -> Result<Value> {
+    let keys = parse_path(path)?;
+    if let Some(value) = lookup(document, &keys) {
+        return Ok(value.clone());
+    }
+
+    let config = deserialize(path, document.clone())?;
+    lookup(&to_value(&config)?, &keys)
+        .cloned()
+        .ok_or_else(|| ConfigError::UnknownKey(path.to_string()))
+}
+
+/// Set the dotted `path` in `config` to `value`
+///
+/// `value` is read as YAML, so lists and tags can be given, e.g. `[1.21.1, 1.21.2]`. If that
+/// does not fit the key's type, it is used as a plain string instead.
+pub fn set(config: &mut FerriteConfig, path: &str, value: &str) -> Result<()> {
+    let keys = parse_path(path)?;
+
+    let parsed = serde_norway::from_str(value).unwrap_or_else(|_| Value::String(value.into()));
+    match with_value(config, path, &keys, parsed) {
+        Err(ConfigError::InvalidValue(..)) => {
+            with_value(config, path, &keys, Value::String(value.to_string()))
+        }
+        result => result,
+    }
+}
+
+/// Remove the dotted `path` from `config`, which only works for optional keys and map entries
+pub fn unset(config: &mut FerriteConfig, path: &str) -> Result<()> {
+    let keys = parse_path(path)?;
+    let (key, parent) = keys
+        .split_last()
+        .ok_or_else(|| ConfigError::UnknownKey(path.to_string()))?;
+
+    let mut current = to_value(config)?;
+    match lookup_mut(&mut current, parent) {
+        Some(Value::Mapping(map)) if map.shift_remove(key.as_str()).is_some() => {}
+        _ => return Err(ConfigError::UnknownKey(path.to_string())),
+    }
+
+    let replaced = deserialize(path, current)?;
+    replace(config, replaced);
+    Ok(())
+}
+
+/// Set `keys` to `value` in `config`, if the result is still a valid config
+fn with_value(config: &mut FerriteConfig, path: &str, keys: &[String], value: Value) -> Result<()> {
+    let mut current = to_value(config)?;
+    *lookup_or_insert(&mut current, keys) = value.clone();
+
+    // Keys that are not part of the config are dropped when deserializing, so make sure the
+    // value survived the round trip
+    let replaced = deserialize(path, current)?;
+    if lookup(&to_value(&replaced)?, keys) != Some(&value) {
+        return Err(ConfigError::UnknownKey(path.to_string()));
+    }
+
+    replace(config, replaced);
+    Ok(())
+}
+
+/// Type-check `value` after changing `path` in it
+fn deserialize(path: &str, value: Value) -> Result<FerriteConfig> {
+    serde_norway::from_value(value)
+        .map_err(|e| ConfigError::InvalidValue(path.to_string(), e.to_string()))
+}
+
+/// Replace `config` with `replaced`, keeping where it was loaded from and its environment
+fn replace(config: &mut FerriteConfig, mut replaced: FerriteConfig) {
+    replaced.source = config.source.take();
+    replaced.path = mem::take(&mut config.path);
+    replaced.environment = config.environment.take();
+    *config = replaced;
+}
+
+/// Format `value` for printing, scalars as they are and everything else as YAML
+pub fn display(value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::Null => String::new(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => value.clone(),
+        value => serde_norway::to_string(value)?.trim_end().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{set, unset};
+    use crate::config::FerriteConfig;
+    use libium::config::structs::ModLoader;
+    use std::path::{Path, PathBuf};
+
+    fn staging() -> FerriteConfig {
+        let mut config = FerriteConfig::new(
+            vec!["1.21.1".to_string()],
+            vec![ModLoader::Fabric],
+            "java -jar {}".to_string(),
+            "server.jar".to_string(),
+        );
+        config.path = PathBuf::from("server/ferrite.yaml");
+        config.environment = Some("staging".to_string());
+        config
+    }
+
+    #[test]
+    fn keeps_the_environment_lock() {
+        let mut config = staging();
+        set(&mut config, "autoupdate", "false").unwrap();
+        assert!(!config.autoupdate);
+        assert_eq!(config.lock_path(), Path::new("server/ferrite.staging.lock"));
+
+        set(&mut config, "key_store_entries.github_token", "github").unwrap();
+        unset(&mut config, "key_store_entries.github_token").unwrap();
+        assert_eq!(config.path, Path::new("server/ferrite.yaml"));
+        assert_eq!(config.lock_path(), Path::new("server/ferrite.staging.lock"));
+    }
+}
@@ -0,0 +1,54 @@
+use super::error::{ConfigError, Result};
+use super::patch::{is_keyed_list, same_item};
+use serde_norway::Value;
+
+/// Merge `environments.<name>` over the rest of `root` and drop the `environments` section
+///
+/// Mappings are merged recursively, mods are added to the base lists (replacing entries with
+/// the same identifier) and any other value replaces the base value
+pub fn apply_environment(root: &mut Value, name: Option<&str>) -> Result<()> {
+    let environments = match root {
+        Value::Mapping(map) => map.remove("environments"),
+        _ => None,
+    };
+
+    let Some(name) = name else {
+        return Ok(());
+    };
+
+    let overlay = environments
+        .and_then(|mut environments| environments.get_mut(name).map(std::mem::take))
+        .ok_or_else(|| ConfigError::UnknownEnvironment(name.to_string()))?;
+
+    merge(root, overlay, &mut Vec::new());
+
+    Ok(())
+}
+
+fn merge(base: &mut Value, overlay: Value, path: &mut Vec<String>) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match (base.get_mut(&key), key.as_str()) {
+                    (Some(base_value), Some(key)) => {
+                        path.push(key.to_string());
+                        merge(base_value, value, path);
+                        path.pop();
+                    }
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(overlay)) if is_keyed_list(path) => {
+            for item in overlay {
+                match base.iter().position(|b| same_item(b, &item)) {
+                    Some(index) => base[index] = item,
+                    None => base.push(item),
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
@@ -9,14 +9,58 @@ pub enum ConfigError {
     #[error("Failed to parse config: {0}")]
     Parse(String),
 
-    #[error("Failed to run pass command: {0}")]
-    PassCommand(String),
+    #[error("Failed to run key store command: {0}")]
+    KeyStoreCommand(String),
+
+    #[error("Failed to read key store: {0}")]
+    KeyStore(String),
 
     #[error("Failed to get current directory")]
     CurrentDirectory,
 
+    #[error("No ferrite.yaml found in {0} or any of its parents, run `ferrite init` to create one")]
+    NotFound(String),
+
     #[error("Failed to serialize config: {0}")]
     Serialize(String),
+
+    #[error("Environment '{0}' is not defined in ferrite.yaml")]
+    UnknownEnvironment(String),
+
+    #[error("'{0}' is not defined in ferrite.yaml")]
+    EntryNotFound(String),
+
+    #[error("'{0}' is inherited from {1}, edit that file instead")]
+    Inherited(String, String),
+
+    #[error("{0} includes itself")]
+    IncludeCycle(String),
+
+    #[error("Environment variable '{0}' is not set and has no default")]
+    MissingVariable(String),
+
+    #[error("Secret '{0}' was not found in the key store")]
+    MissingSecret(String),
+
+    #[error("Unterminated '${{' in '{0}'")]
+    Interpolation(String),
+
+    #[error(
+        "Config version {0} is newer than the latest version this ferrite supports ({1}), update ferrite"
+    )]
+    UnsupportedVersion(i64, i64),
+
+    #[error("Failed to migrate config to version {0}: {1}")]
+    Migration(i64, String),
+
+    #[error("Failed to convert config: {0}")]
+    Convert(String),
+
+    #[error("Unknown config key `{0}`")]
+    UnknownKey(String),
+
+    #[error("Invalid value for `{0}`: {1}")]
+    InvalidValue(String, String),
 }
 
 impl From<serde_norway::Error> for ConfigError {
@@ -0,0 +1,281 @@
+use super::error::{ConfigError, Result};
+use super::patch::untag;
+use serde_norway::{Value, value::TaggedValue};
+use std::path::Path;
+use toml_edit::{DocumentMut, InlineTable, Item, Table};
+
+/// Variants that are written as tags in YAML, e.g. `!ModrinthProject AANobbMI`
+///
+/// TOML and JSON have no tags, so these are written as a table with the variant as its only
+/// key instead, e.g. `{ ModrinthProject = "AANobbMI" }`
+const TAGS: [&str; 5] = [
+    "ModrinthProject",
+    "CurseForgeProject",
+    "GitHubRepository",
+    "Command",
+    "EncryptedFile",
+];
+
+/// The format of a config file, picked by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl Format {
+    /// The format of the file at `path`, YAML unless it ends in `.toml` or `.json`
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::Toml,
+            Some("json") => Self::Json,
+            _ => Self::Yaml,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Json => "json",
+        }
+    }
+
+    /// Parse `content` into a document, with identifiers represented as YAML tags
+    pub fn parse(self, content: &str) -> Result<Value> {
+        match self {
+            Self::Yaml => Ok(serde_norway::from_str(content)?),
+            Self::Toml => toml_edit::de::from_str(content)
+                .map(retag)
+                .map_err(|e| ConfigError::Parse(e.to_string())),
+            Self::Json => serde_json::from_str(content)
+                .map(retag)
+                .map_err(|e| ConfigError::Parse(e.to_string())),
+        }
+    }
+
+    /// Serialize a whole document, with the schema modeline for YAML
+    pub fn render(self, document: &Value) -> Result<String> {
+        match self {
+            Self::Yaml => Ok(format!(
+                "# yaml-language-server: $schema=https://raw.githubusercontent.com/septechx/ferrite/master/schema/ferrite.schema.json\n{}",
+                serde_norway::to_string(document)?
+            )),
+            Self::Toml => {
+                // TOML has no null, a missing key reads the same
+                let mut document = untag(document);
+                remove_nulls(&mut document);
+
+                let mut toml: DocumentMut = toml_edit::ser::to_document(&document)
+                    .map_err(|e| ConfigError::Serialize(e.to_string()))?;
+                expand_tables(toml.as_table_mut());
+                Ok(toml.to_string())
+            }
+            Self::Json => serde_json::to_string_pretty(&untag(document))
+                .map(|json| json + "\n")
+                .map_err(|e| ConfigError::Serialize(e.to_string())),
+        }
+    }
+}
+
+/// Turn tables whose only key is a variant in `TAGS` back into tags
+///
+/// Only the values the schema declares as tagged enums are retagged: `key_store`, the
+/// identifiers of `ferium.mods` and `ferium.disabled`, `ferium.overrides` and the same keys
+/// under `environments`. Anything else, like a git command named `Command`, is kept as it is.
+fn retag(mut root: Value) -> Value {
+    retag_root(&mut root);
+    root
+}
+
+fn retag_root(root: &mut Value) {
+    if let Some(key_store) = root.get_mut("key_store") {
+        retag_value(key_store);
+    }
+
+    if let Some(ferium) = root.get_mut("ferium") {
+        if let Some(Value::Mapping(overrides)) = ferium.get_mut("overrides") {
+            overrides
+                .iter_mut()
+                .for_each(|(_, identifier)| retag_value(identifier));
+        }
+        for list in ["mods", "disabled"] {
+            if let Some(Value::Sequence(mods)) = ferium.get_mut(list) {
+                mods.iter_mut()
+                    .filter_map(|mod_| mod_.get_mut("identifier"))
+                    .for_each(retag_value);
+            }
+        }
+    }
+
+    if let Some(Value::Mapping(environments)) = root.get_mut("environments") {
+        environments
+            .iter_mut()
+            .for_each(|(_, environment)| retag_root(environment));
+    }
+}
+
+/// Turn `value` into a tag if it is a table whose only key is a variant in `TAGS`
+fn retag_value(value: &mut Value) {
+    if let Value::Mapping(map) = value
+        && map.len() == 1
+        && let Some((Value::String(key), _)) = map.iter().next()
+        && TAGS.contains(&key.as_str())
+    {
+        let (key, inner) = std::mem::take(map).into_iter().next().unwrap_or_default();
+        *value = Value::Tagged(Box::new(TaggedValue {
+            tag: serde_norway::value::Tag::new(key.as_str().unwrap_or_default()),
+            value: inner,
+        }));
+    }
+}
+
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Mapping(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.iter_mut().for_each(|(_, value)| remove_nulls(value));
+        }
+        Value::Sequence(seq) => seq.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+fn is_tag(table: &InlineTable) -> bool {
+    table.len() == 1 && table.iter().all(|(key, _)| TAGS.contains(&key))
+}
+
+/// Write nested tables and lists of tables as `[sections]`, keeping tags inline
+fn expand_tables(table: &mut Table) {
+    for (_, item) in table.iter_mut() {
+        let expanded = match item {
+            Item::Value(toml_edit::Value::InlineTable(inline)) if !is_tag(inline) => {
+                Item::Table(std::mem::take(inline).into_table())
+            }
+            Item::Value(toml_edit::Value::Array(array))
+                if !array.is_empty()
+                    && array
+                        .iter()
+                        .all(|value| value.as_inline_table().is_some_and(|t| !is_tag(t))) =>
+            {
+                Item::ArrayOfTables(
+                    array
+                        .iter()
+                        .filter_map(toml_edit::Value::as_inline_table)
+                        .map(|inline| inline.clone().into_table())
+                        .collect(),
+                )
+            }
+            _ => continue,
+        };
+        *item = expanded;
+
+        match item {
+            Item::Table(table) => {
+                expand_tables(table);
+                // A table that only holds other tables doesn't need its own header
+                let implicit = !table.is_empty() && table.iter().all(|(_, item)| item.is_table());
+                table.set_implicit(implicit);
+            }
+            Item::ArrayOfTables(tables) => tables.iter_mut().for_each(expand_tables),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+    use serde_norway::Value;
+
+    fn yaml(content: &str) -> Value {
+        serde_norway::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn retags_identifiers_and_key_store() {
+        let json = r#"{
+            "key_store": { "Command": "pass show {}" },
+            "ferium": {
+                "overrides": { "fabric-api": { "ModrinthProject": "P7dR8mSH" } },
+                "mods": [{ "name": "Sodium", "identifier": { "ModrinthProject": "AANobbMI" } }],
+                "disabled": [{ "name": "JEI", "identifier": { "CurseForgeProject": 238222 } }]
+            },
+            "environments": {
+                "dev": {
+                    "ferium": {
+                        "mods": [{
+                            "name": "Carpet",
+                            "identifier": { "GitHubRepository": ["gnembon", "fabric-carpet"] }
+                        }]
+                    }
+                }
+            }
+        }"#;
+
+        assert_eq!(
+            Format::Json.parse(json).unwrap(),
+            yaml(
+                "key_store: !Command pass show {}
+ferium:
+  overrides:
+    fabric-api: !ModrinthProject P7dR8mSH
+  mods:
+    - name: Sodium
+      identifier: !ModrinthProject AANobbMI
+  disabled:
+    - name: JEI
+      identifier: !CurseForgeProject 238222
+environments:
+  dev:
+    ferium:
+      mods:
+        - name: Carpet
+          identifier: !GitHubRepository [gnembon, fabric-carpet]
+"
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_other_tables() {
+        let toml = r#"
+[git.commands]
+Command = "stage"
+
+[ferium]
+mods = [{ name = "Sodium", identifier = { ModrinthProject = "AANobbMI" }, tags = [] }]
+"#;
+
+        assert_eq!(
+            Format::Toml.parse(toml).unwrap(),
+            yaml(
+                "git:
+  commands:
+    Command: stage
+ferium:
+  mods:
+    - name: Sodium
+      identifier: !ModrinthProject AANobbMI
+      tags: []
+"
+            )
+        );
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let document = yaml(
+            "key_store: !EncryptedFile secrets.age
+ferium:
+  mods:
+    - name: Sodium
+      identifier: !ModrinthProject AANobbMI
+",
+        );
+
+        let toml = Format::Toml.render(&document).unwrap();
+        assert_eq!(Format::Toml.parse(&toml).unwrap(), document);
+    }
+}
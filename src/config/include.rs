@@ -0,0 +1,182 @@
+use super::error::{ConfigError, Result};
+use super::format::Format;
+use super::patch::{same_item, untag};
+use super::upgrade::migrate;
+use serde_norway::{Mapping, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Entries that were merged in from included files, and the file each one came from
+#[derive(Debug, Clone, Default)]
+pub struct Inherited {
+    mods: Vec<(Value, PathBuf)>,
+    overrides: Vec<(String, PathBuf)>,
+}
+
+impl Inherited {
+    /// The file a mod with `identifier` was inherited from
+    pub fn mod_origin(&self, identifier: &Value) -> Option<&Path> {
+        let identifier = untag(identifier);
+        self.mods
+            .iter()
+            .find(|(key, _)| *key == identifier)
+            .map(|(_, path)| path.as_path())
+    }
+
+    /// The file the override for `key` was inherited from
+    pub fn override_origin(&self, key: &str) -> Option<&Path> {
+        self.overrides
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, path)| path.as_path())
+    }
+}
+
+/// The merged `ferium` lists, along with the origin of each entry
+///
+/// An origin of `None` means the entry comes from the top-level file
+#[derive(Default)]
+struct Merged {
+    mods: Vec<Value>,
+    disabled: Vec<Value>,
+    overrides: Mapping,
+    mod_origins: Vec<(Value, Option<PathBuf>)>,
+    override_origins: Vec<(String, Option<PathBuf>)>,
+}
+
+impl Merged {
+    /// Merge the `ferium` section of `root` over the entries merged so far
+    fn merge(&mut self, root: &Value, origin: Option<&Path>) {
+        let list = |key: &str| {
+            root.get("ferium")
+                .and_then(|ferium| ferium.get(key))
+                .and_then(Value::as_sequence)
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        // A mod enabled in a later file is no longer disabled, and vice versa
+        for item in list("mods") {
+            self.disabled.retain(|d| !same_item(d, &item));
+            self.set_mod_origin(&item, origin);
+            upsert(&mut self.mods, item);
+        }
+        for item in list("disabled") {
+            self.mods.retain(|m| !same_item(m, &item));
+            self.set_mod_origin(&item, origin);
+            upsert(&mut self.disabled, item);
+        }
+
+        if let Some(overrides) = root
+            .get("ferium")
+            .and_then(|ferium| ferium.get("overrides"))
+            .and_then(Value::as_mapping)
+        {
+            for (key, value) in overrides {
+                if let Some(key) = key.as_str() {
+                    self.override_origins.retain(|(k, _)| k != key);
+                    self.override_origins
+                        .push((key.to_string(), origin.map(Path::to_path_buf)));
+                }
+                self.overrides.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    fn set_mod_origin(&mut self, item: &Value, origin: Option<&Path>) {
+        if let Some(identifier) = item.get("identifier") {
+            let identifier = untag(identifier);
+            self.mod_origins.retain(|(key, _)| *key != identifier);
+            self.mod_origins
+                .push((identifier, origin.map(Path::to_path_buf)));
+        }
+    }
+}
+
+fn upsert(items: &mut Vec<Value>, item: Value) {
+    match items.iter().position(|i| same_item(i, &item)) {
+        Some(index) => items[index] = item,
+        None => items.push(item),
+    }
+}
+
+/// Merge the mods, overrides and disabled mods of the files listed in `include` under `root`
+///
+/// Included files are resolved relative to `dir` and may include other files themselves.
+/// Files listed later win over earlier ones, and `root` wins over all of them.
+pub fn resolve_includes(root: &mut Value, dir: &Path) -> Result<Inherited> {
+    if root.get("include").is_none() {
+        return Ok(Inherited::default());
+    }
+
+    let mut merged = Merged::default();
+    merge_includes(root, dir, &mut merged, &mut Vec::new())?;
+    merged.merge(root, None);
+
+    if let Value::Mapping(map) = root {
+        map.remove("include");
+    }
+    let ferium = super::patch::lookup_or_insert(root, &["ferium".to_string()]);
+    if let Value::Mapping(ferium) = ferium {
+        ferium.insert("mods".into(), Value::Sequence(merged.mods));
+        ferium.insert("disabled".into(), Value::Sequence(merged.disabled));
+        ferium.insert("overrides".into(), Value::Mapping(merged.overrides));
+    }
+
+    Ok(Inherited {
+        mods: merged
+            .mod_origins
+            .into_iter()
+            .filter_map(|(key, origin)| Some((key, origin?)))
+            .collect(),
+        overrides: merged
+            .override_origins
+            .into_iter()
+            .filter_map(|(key, origin)| Some((key, origin?)))
+            .collect(),
+    })
+}
+
+fn merge_includes(
+    root: &Value,
+    dir: &Path,
+    merged: &mut Merged,
+    stack: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let includes = root
+        .get("include")
+        .and_then(Value::as_sequence)
+        .cloned()
+        .unwrap_or_default();
+
+    for include in includes {
+        let include = include
+            .as_str()
+            .ok_or_else(|| ConfigError::Parse("`include` entries must be paths".to_string()))?;
+        let path = dir.join(include);
+        let canonical = path.canonicalize()?;
+
+        if stack.contains(&canonical) {
+            return Err(ConfigError::IncludeCycle(path.display().to_string()));
+        }
+
+        let format = Format::of(&path);
+        let content = migrate(&fs::read_to_string(&path)?, format)?.content;
+        let included = format.parse(&content)?;
+
+        stack.push(canonical);
+        merge_includes(
+            &included,
+            path.parent().unwrap_or(Path::new("")),
+            merged,
+            stack,
+        )?;
+        stack.pop();
+
+        merged.merge(&included, Some(&path));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use super::error::{ConfigError, Result};
+use super::key_store::Secrets;
+use serde_norway::Value;
+use std::env;
+
+/// Expand `${VAR}`, `${VAR:-default}` and `${secret:NAME}` in every string of `value`
+///
+/// `$${` is kept as a literal `${`
+pub fn interpolate(value: &mut Value, secrets: &Secrets) -> Result<()> {
+    match value {
+        Value::String(s) if s.contains("${") => *s = expand(s, secrets)?,
+        Value::Sequence(seq) => {
+            for item in seq {
+                interpolate(item, secrets)?;
+            }
+        }
+        Value::Mapping(map) => {
+            for (_, item) in map.iter_mut() {
+                interpolate(item, secrets)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate(&mut tagged.value, secrets)?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn expand(input: &str, secrets: &Secrets) -> Result<String> {
+    let mut output = String::new();
+    let mut rest = input;
+
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        output.push_str(&rest[..start]);
+        let end = start
+            + rest[start..]
+                .find('}')
+                .ok_or_else(|| ConfigError::Interpolation(input.to_string()))?;
+        output.push_str(&resolve(&rest[start + 2..end], secrets)?);
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+fn resolve(expression: &str, secrets: &Secrets) -> Result<String> {
+    if let Some(name) = expression.strip_prefix("secret:") {
+        return secrets.require(name.trim());
+    }
+
+    let (name, default) = match expression.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (expression, None),
+    };
+
+    // Like in a shell, the default is also used for empty variables
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => Ok(value),
+        _ => default
+            .map(str::to_string)
+            .ok_or_else(|| ConfigError::MissingVariable(name.to_string())),
+    }
+}
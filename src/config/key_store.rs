@@ -0,0 +1,241 @@
+use super::error::{ConfigError, Result};
+use super::types::KeyStoreConfig;
+use age::secrecy::SecretString;
+use serde_norway::Value;
+use std::{
+    cell::OnceCell,
+    collections::HashMap,
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Secrets that are exported for libium, and the variables they are exported as
+const API_KEYS: [(&str, &str); 2] = [
+    ("github_token", "GITHUB_TOKEN"),
+    ("curseforge_api_key", "CURSEFORGE_API_KEY"),
+];
+
+/// Variable the passphrase of an encrypted secrets file is read from before prompting for it
+const PASSPHRASE_VARIABLE: &str = "FERRITE_PASSPHRASE";
+
+/// A backend that secrets are read from
+pub trait KeyStore {
+    /// The entry the secret `name` is read from when `key_store_entries` does not configure one
+    fn default_entry(&self, name: &str) -> String {
+        name.to_string()
+    }
+
+    /// Read `entry` from the store, `None` if it does not exist
+    fn read(&self, entry: &str) -> Result<Option<String>>;
+}
+
+/// Reads `ferrite/<name>` from the `pass` password store
+struct Pass;
+
+impl KeyStore for Pass {
+    fn default_entry(&self, name: &str) -> String {
+        format!("ferrite/{name}")
+    }
+
+    fn read(&self, entry: &str) -> Result<Option<String>> {
+        run(&["pass", entry], entry, None)
+    }
+}
+
+/// Reads upper-cased variables from the environment
+struct Environment;
+
+impl KeyStore for Environment {
+    fn default_entry(&self, name: &str) -> String {
+        name.to_uppercase()
+    }
+
+    fn read(&self, entry: &str) -> Result<Option<String>> {
+        Ok(env::var(entry).ok().filter(|value| !value.is_empty()))
+    }
+}
+
+/// Runs a command for every secret, with `{}` in its arguments replaced by the entry
+///
+/// The command is split into arguments like a shell does, so quoted arguments may hold spaces.
+/// It runs in the config's directory, so it may be a script next to the config.
+struct CommandStore {
+    command: String,
+    dir: PathBuf,
+}
+
+impl KeyStore for CommandStore {
+    fn read(&self, entry: &str) -> Result<Option<String>> {
+        let args: Vec<String> = shell_words::split(&self.command)
+            .map_err(|e| ConfigError::KeyStoreCommand(format!("{}: {e}", self.command)))?
+            .into_iter()
+            .map(|arg| arg.replace("{}", entry))
+            .collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run(&args, entry, Some(&self.dir))
+    }
+}
+
+/// Reads a YAML mapping of entries from an age file encrypted with a passphrase
+///
+/// The file is only decrypted once a secret is needed, and at most once
+struct EncryptedFile {
+    path: PathBuf,
+    entries: OnceCell<HashMap<String, String>>,
+}
+
+impl EncryptedFile {
+    fn decrypt(&self) -> Result<HashMap<String, String>> {
+        let encrypted = fs::read(&self.path)?;
+
+        let passphrase = match env::var(PASSPHRASE_VARIABLE) {
+            Ok(passphrase) => passphrase,
+            Err(_) => inquire::Password::new(&format!("Passphrase for {}:", self.path.display()))
+                .without_confirmation()
+                .prompt()
+                .map_err(|e| ConfigError::KeyStore(e.to_string()))?,
+        };
+
+        let identity = age::scrypt::Identity::new(SecretString::from(passphrase));
+        let decrypted = age::decrypt(&identity, &encrypted)
+            .map_err(|e| ConfigError::KeyStore(format!("{}: {e}", self.path.display())))?;
+
+        let entries: HashMap<String, Value> = serde_norway::from_slice(&decrypted)
+            .map_err(|e| ConfigError::KeyStore(format!("{}: {e}", self.path.display())))?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|(entry, value)| match value {
+                Value::String(value) => Some((entry, value)),
+                Value::Number(value) => Some((entry, value.to_string())),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+impl KeyStore for EncryptedFile {
+    fn read(&self, entry: &str) -> Result<Option<String>> {
+        let entries = match self.entries.get() {
+            Some(entries) => entries,
+            None => {
+                let entries = self.decrypt()?;
+                self.entries.get_or_init(|| entries)
+            }
+        };
+
+        Ok(entries.get(entry).cloned())
+    }
+}
+
+/// Load the `.env` in `dir` into the environment, creating it if it does not exist
+fn load_dotenv(dir: &Path) -> Result<()> {
+    let path = dir.join(".env");
+    if !fs::exists(&path)? {
+        let mut file = fs::File::create(&path)?;
+        file.write_all("# https://github.com/septechx/ferrite/blob/master/schema/.env".as_bytes())?;
+    };
+
+    dotenvy::from_path(&path).ok();
+
+    Ok(())
+}
+
+/// Run `args` in `dir`, returning its trimmed output, or `None` if it failed
+fn run(args: &[&str], entry: &str, dir: Option<&Path>) -> Result<Option<String>> {
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| ConfigError::KeyStore("the command is empty".to_string()))?;
+
+    let mut command = Command::new(program);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .map_err(|e| ConfigError::KeyStoreCommand(format!("{program} ({entry}): {e}")))?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(secret).filter(|secret| !secret.is_empty()))
+}
+
+/// The API key `name` as it was exported for libium, `None` if the key store does not have it
+///
+/// For the few requests libium has no call for, so they use the same key as libium does
+pub fn api_key(name: &str) -> Option<String> {
+    API_KEYS
+        .iter()
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, variable)| env::var(variable).ok())
+        .filter(|key| !key.is_empty())
+}
+
+/// The configured key store, with the entries secrets are read from
+pub struct Secrets {
+    store: Box<dyn KeyStore>,
+    entries: HashMap<String, String>,
+}
+
+impl Secrets {
+    /// Open `key_store`, with the paths it reads from relative to `dir`
+    pub fn open(
+        key_store: &KeyStoreConfig,
+        entries: HashMap<String, String>,
+        dir: &Path,
+    ) -> Result<Self> {
+        let store: Box<dyn KeyStore> = match key_store {
+            KeyStoreConfig::Pass => Box::new(Pass),
+            KeyStoreConfig::DotEnv => {
+                load_dotenv(dir)?;
+                Box::new(Environment)
+            }
+            KeyStoreConfig::Env => Box::new(Environment),
+            KeyStoreConfig::Command(command) => Box::new(CommandStore {
+                command: command.clone(),
+                dir: dir.to_path_buf(),
+            }),
+            KeyStoreConfig::EncryptedFile(path) => Box::new(EncryptedFile {
+                path: dir.join(path),
+                entries: OnceCell::new(),
+            }),
+        };
+
+        Ok(Self { store, entries })
+    }
+
+    /// Read the secret `name`, `None` if the store does not have it
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        let entry = match self.entries.get(name) {
+            Some(entry) => entry.clone(),
+            None => self.store.default_entry(name),
+        };
+        self.store.read(&entry)
+    }
+
+    /// Read the secret `name`, failing if the store does not have it
+    pub fn require(&self, name: &str) -> Result<String> {
+        self.get(name)?
+            .ok_or_else(|| ConfigError::MissingSecret(name.to_string()))
+    }
+
+    /// Make the API keys available to libium through the environment
+    ///
+    /// Keys the store does not have are left as they are
+    pub fn export(&self) -> Result<()> {
+        for (name, variable) in API_KEYS {
+            if let Some(value) = self.get(name)? {
+                unsafe {
+                    env::set_var(variable, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,81 +1,256 @@
+mod document;
+mod edit;
+mod environment;
 mod error;
+mod format;
+mod include;
+mod interpolate;
+mod key_store;
+mod patch;
+mod schema;
+mod source;
 mod types;
 mod upgrade;
+mod validate;
 
+pub use edit::{display, get, set, unset};
 pub use error::{ConfigError, Result};
-pub use types::{FerriteConfig, KeyStoreConfig};
-pub use upgrade::{detect_config_version, needs_upgrade, upgrade_config};
+pub use format::Format;
+pub use key_store::api_key;
+pub use schema::schema;
+pub use types::{FeriumConfig, FerriteConfig, GitMode, KeyStoreConfig, ModConfig, Side};
+pub use validate::{Severity, validate};
 
 use colored::Colorize;
-use dotenvy::dotenv;
-use std::{env, fs, io::Write, process::Command};
+use document::Document;
+use environment::apply_environment;
+use include::resolve_includes;
+use interpolate::interpolate;
+use key_store::Secrets;
+use serde_norway::Value;
+use source::{ConfigSource, to_value};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use upgrade::{Migration, migrate};
 
-pub fn load_config() -> Result<FerriteConfig> {
-    let mut config_content = fs::read_to_string("ferrite.yaml")?;
-    let mut version = detect_config_version(&config_content);
-    let original_version = version;
+/// Name of the config file that is created by default
+pub const CONFIG_FILE: &str = "ferrite.yaml";
 
-    if needs_upgrade(version) {
+/// Names of the config files that are searched for, in order of preference
+const CONFIG_FILES: [&str; 3] = [CONFIG_FILE, "ferrite.toml", "ferrite.json"];
+
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Environment from `environments` to merge over the base config
+    pub environment: Option<String>,
+    /// Config file to use instead of searching for one
+    pub path: Option<PathBuf>,
+}
+
+/// Find the config file
+///
+/// Without an explicit `path`, the current directory and then its parents are searched for
+/// ferrite.yaml, ferrite.toml or ferrite.json, like git does for `.git`
+pub fn find_config(options: &LoadOptions) -> Result<PathBuf> {
+    let current = env::current_dir().map_err(|_| ConfigError::CurrentDirectory)?;
+    match &options.path {
+        Some(path) => Ok(current.join(path)),
+        None => current
+            .ancestors()
+            .flat_map(|directory| CONFIG_FILES.map(|file| directory.join(file)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| ConfigError::NotFound(current.display().to_string())),
+    }
+}
+
+/// The directory of the config file at `path`
+///
+/// Every relative path in the config (`output_path`, `server.executable`, `.env`, includes)
+/// and the files ferrite keeps next to it are resolved against this directory
+pub fn config_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+pub fn load_config(options: &LoadOptions) -> Result<FerriteConfig> {
+    let path = find_config(options)?;
+    let dir = config_dir(&path);
+    let format = Format::of(&path);
+    let config_content = migrate_file(&path)?.content;
+
+    let document = format.parse(&config_content)?;
+    let mut effective = document.clone();
+    let inherited = resolve_includes(&mut effective, dir)?;
+    apply_environment(&mut effective, options.environment.as_deref())?;
+
+    // The key store has to be set up before expanding, as values may refer to its secrets
+    let key_store: KeyStoreConfig = effective
+        .get("key_store")
+        .cloned()
+        .map(serde_norway::from_value)
+        .transpose()?
+        .ok_or_else(|| ConfigError::Parse("missing field `key_store`".to_string()))?;
+    let entries = effective
+        .get("key_store_entries")
+        .cloned()
+        .map(serde_norway::from_value)
+        .transpose()?
+        .unwrap_or_default();
+    let secrets = Secrets::open(&key_store, entries, dir)?;
+    secrets.export()?;
+    interpolate(&mut effective, &secrets)?;
+
+    let mut config: FerriteConfig =
+        serde_norway::from_value(effective).map_err(|e| ConfigError::Parse(e.to_string()))?;
+    // Only YAML is edited in place, other formats are serialized as a whole
+    let document = match format {
+        Format::Yaml => Document::new(&config_content, document),
+        Format::Toml | Format::Json => Document::from_value(document),
+    };
+    config.source = Some(ConfigSource::new(
+        document,
+        to_value(&config)?,
+        options.environment.clone(),
+        inherited,
+    ));
+    config.path = path;
+    config.environment = options.environment.clone();
+
+    Ok(config)
+}
+
+/// The config as it is written, with its includes and environment merged in
+///
+/// Nothing is written, even if the config needs migrating, and `${VAR}` references are kept
+/// as they are instead of being expanded, so the key store is never read
+pub fn read_config(options: &LoadOptions) -> Result<Value> {
+    let path = find_config(options)?;
+    let format = Format::of(&path);
+    let migration = migrate(&fs::read_to_string(&path)?, format)?;
+
+    let mut document = format.parse(&migration.content)?;
+    resolve_includes(&mut document, config_dir(&path))?;
+    apply_environment(&mut document, options.environment.as_deref())?;
+    Ok(document)
+}
+
+/// Read the config like `read_config` and type-check it, to look at a config without using it
+///
+/// `${VAR}` references are kept as they are, so it is not meant to be written back or installed
+pub fn peek_config(options: &LoadOptions) -> Result<FerriteConfig> {
+    let mut config: FerriteConfig = serde_norway::from_value(read_config(options)?)
+        .map_err(|e| ConfigError::Parse(e.to_string()))?;
+    config.path = find_config(options)?;
+    config.environment = options.environment.clone();
+    Ok(config)
+}
+
+/// Migrate the config at `path` to the latest version if needed
+///
+/// The original file is backed up before it is overwritten
+fn migrate_file(path: &Path) -> Result<Migration> {
+    let original = fs::read_to_string(path)?;
+    let migration = migrate(&original, Format::of(path))?;
+
+    if migration.to > migration.from {
         println!(
             "{} Upgrading config from version {} to {}...",
             "⚠".yellow(),
-            original_version,
-            version
+            migration.from,
+            migration.to
+        );
+
+        let backup = backup_path(path, migration.from);
+        fs::write(&backup, &original)?;
+        fs::write(path, &migration.content)?;
+
+        println!(
+            "{} Config upgraded to version {}, the original was saved to {}",
+            "✓".green(),
+            migration.to,
+            backup.display()
         );
     }
 
-    while needs_upgrade(version) {
-        if let Some(upgraded) = upgrade_config(&config_content, version) {
-            config_content = upgraded;
-            version += 1;
-        } else {
-            break;
-        }
+    Ok(migration)
+}
+
+/// `<path>.v<version>.bak`, with a timestamp if that backup already exists
+fn backup_path(path: &Path, version: i64) -> PathBuf {
+    let backup = PathBuf::from(format!("{}.v{version}.bak", path.display()));
+    if !backup.exists() {
+        return backup;
     }
 
-    if version > original_version {
-        fs::write("ferrite.yaml", &config_content)?;
-        println!("{} Config upgraded to version {}.", "✓".green(), version);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    PathBuf::from(format!("{}.v{version}.{timestamp}.bak", path.display()))
+}
+
+/// A unified diff of what migrating the config would change, `None` if it is up to date
+pub fn migration_diff(options: &LoadOptions) -> Result<Option<String>> {
+    let path = find_config(options)?;
+    let original = fs::read_to_string(&path)?;
+    let migration = migrate(&original, Format::of(&path))?;
+
+    if migration.to == migration.from {
+        return Ok(None);
     }
 
-    let config: FerriteConfig =
-        serde_norway::from_str(&config_content).map_err(|e| ConfigError::Parse(e.to_string()))?;
-
-    match config.key_store {
-        KeyStoreConfig::DotEnv => {
-            if !fs::exists(".env")? {
-                let mut file = fs::File::create(".env")?;
-                file.write_all(
-                    "# https://github.com/septechx/ferrite/blob/master/schema/.env".as_bytes(),
-                )?;
-            };
-
-            dotenv().ok();
-        }
-        KeyStoreConfig::Pass => {
-            let gh_token = Command::new("pass")
-                .arg("ferrite/github_token")
-                .output()
-                .map_err(|e| ConfigError::PassCommand(format!("github_token: {}", e)))?
-                .stdout;
-
-            let token_str = String::from_utf8_lossy(&gh_token).trim().to_string();
-            unsafe {
-                env::set_var("GITHUB_TOKEN", token_str);
-            }
-
-            let cf_api_key = Command::new("pass")
-                .arg("ferrite/curseforge_api_key")
-                .output()
-                .map_err(|e| ConfigError::PassCommand(format!("curseforge_api_key: {}", e)))?
-                .stdout;
-
-            let key_str = String::from_utf8_lossy(&cf_api_key).trim().to_string();
-            unsafe {
-                env::set_var("CURSEFORGE_API_KEY", key_str);
-            }
-        }
+    Ok(Some(unified_diff(
+        &original,
+        &migration.content,
+        &format!("{} (version {})", path.display(), migration.from),
+        &format!("{} (version {})", path.display(), migration.to),
+    )))
+}
+
+/// Migrate the config to the latest version, returning whether it was changed
+pub fn migrate_config(options: &LoadOptions) -> Result<bool> {
+    let migration = migrate_file(&find_config(options)?)?;
+    Ok(migration.to > migration.from)
+}
+
+/// Render a unified diff between `old` and `new`
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(old_name, new_name)
+        .to_string()
+}
+
+/// Convert the config to `format`, returning the path of the new file
+///
+/// The original file is kept as a backup, since only one config can be used per directory
+pub fn convert_config(options: &LoadOptions, format: Format) -> Result<PathBuf> {
+    let path = find_config(options)?;
+    let from = Format::of(&path);
+    if from == format {
+        return Err(ConfigError::Convert(format!(
+            "{} is already {}",
+            path.display(),
+            format.extension()
+        )));
     }
 
-    Ok(config)
+    let converted = path.with_extension(format.extension());
+    if converted.exists() {
+        return Err(ConfigError::Convert(format!(
+            "{} already exists",
+            converted.display()
+        )));
+    }
+
+    let document = from.parse(&migrate_file(&path)?.content)?;
+    fs::write(&converted, format.render(&document)?)?;
+    fs::rename(&path, format!("{}.bak", path.display()))?;
+
+    Ok(converted)
 }
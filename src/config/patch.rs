@@ -0,0 +1,207 @@
+use super::document::Document;
+use super::error::{ConfigError, Result};
+use serde_norway::{Mapping, Value};
+
+/// Lists whose items are matched by their `identifier` rather than by their position
+const KEYED_LISTS: [[&str; 2]; 2] = [["ferium", "mods"], ["ferium", "disabled"]];
+const ITEM_KEY: &str = "identifier";
+
+/// A single edit to a config document
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// Set the value at the path
+    Set(Vec<String>, Value),
+    /// Remove the key at the path
+    Remove(Vec<String>),
+    /// Append an item to the keyed list at the path
+    Insert(Vec<String>, Value),
+    /// Remove an item from the keyed list at the path
+    Delete(Vec<String>, Value),
+    /// Replace the item with the same key in the keyed list at the path
+    Replace(Vec<String>, Value),
+}
+
+pub fn is_keyed_list(path: &[String]) -> bool {
+    KEYED_LISTS.iter().any(|list| {
+        list.len() == path.len() && list.iter().zip(path).all(|(a, b)| *a == b.as_str())
+    })
+}
+
+/// Represent YAML tags as single-key mappings, which is how serde reads enums from other formats
+pub fn untag(value: &Value) -> Value {
+    match value {
+        Value::Tagged(tagged) => Value::Mapping(
+            [(
+                Value::String(tagged.tag.to_string().trim_start_matches('!').to_string()),
+                untag(&tagged.value),
+            )]
+            .into_iter()
+            .collect(),
+        ),
+        Value::Sequence(seq) => Value::Sequence(seq.iter().map(untag).collect()),
+        Value::Mapping(map) => {
+            Value::Mapping(map.iter().map(|(k, v)| (untag(k), untag(v))).collect())
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Whether `a` and `b` are items of a keyed list that refer to the same mod
+pub fn same_item(a: &Value, b: &Value) -> bool {
+    match (a.get(ITEM_KEY), b.get(ITEM_KEY)) {
+        (Some(a), Some(b)) => untag(a) == untag(b),
+        _ => false,
+    }
+}
+
+/// Find the changes that turn `old` into `new`
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_at(&mut Vec::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_at(path: &mut Vec<String>, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Mapping(old_map), Value::Mapping(new_map))
+            if old_map.keys().chain(new_map.keys()).all(Value::is_string) =>
+        {
+            for (key, new_value) in new_map {
+                path.push(key.as_str().unwrap_or_default().to_string());
+                match old_map.get(key) {
+                    Some(old_value) => diff_at(path, old_value, new_value, changes),
+                    None => changes.push(Change::Set(path.clone(), new_value.clone())),
+                }
+                path.pop();
+            }
+            for key in old_map.keys().filter(|key| !new_map.contains_key(*key)) {
+                path.push(key.as_str().unwrap_or_default().to_string());
+                changes.push(Change::Remove(path.clone()));
+                path.pop();
+            }
+        }
+        (Value::Sequence(old_items), Value::Sequence(new_items)) if is_keyed_list(path) => {
+            for item in old_items {
+                if !new_items.iter().any(|new_item| same_item(new_item, item)) {
+                    changes.push(Change::Delete(path.clone(), item.clone()));
+                }
+            }
+            for item in new_items {
+                match old_items.iter().find(|old_item| same_item(old_item, item)) {
+                    None => changes.push(Change::Insert(path.clone(), item.clone())),
+                    Some(old_item) if old_item != item => {
+                        changes.push(Change::Replace(path.clone(), item.clone()))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        _ if old != new => changes.push(Change::Set(path.clone(), new.clone())),
+        _ => {}
+    }
+}
+
+/// Apply `changes` to `document`
+///
+/// With an active `environment`, values that the environment overrides and new list items
+/// are written to `environments.<name>` instead of the base config
+pub fn apply(
+    document: &mut Document,
+    changes: Vec<Change>,
+    environment: Option<&str>,
+) -> Result<()> {
+    let environment_prefix = environment
+        .map(|name| vec!["environments".to_string(), name.to_string()])
+        .unwrap_or_default();
+
+    for change in changes {
+        match change {
+            Change::Set(path, value) => {
+                let prefix = value_layer(document.value(), &environment_prefix, &path);
+                document.set(&[prefix, path].concat(), value);
+            }
+            Change::Remove(path) => {
+                let prefix = value_layer(document.value(), &environment_prefix, &path);
+                document.remove(&[prefix, path].concat());
+            }
+            Change::Insert(path, item) => {
+                document.push(&[environment_prefix.clone(), path].concat(), item);
+            }
+            Change::Delete(path, item) => {
+                let (path, index) = find_item(document.value(), &environment_prefix, &path, &item)?;
+                document.remove_item(&path, index);
+            }
+            Change::Replace(path, item) => {
+                let (path, index) = find_item(document.value(), &environment_prefix, &path, &item)?;
+                document.replace_item(&path, index, item);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The layer a value at `path` is written to, the environment if it overrides `path`
+fn value_layer(document: &Value, environment_prefix: &[String], path: &[String]) -> Vec<String> {
+    if !environment_prefix.is_empty()
+        && lookup(document, &[environment_prefix, path].concat()).is_some()
+    {
+        environment_prefix.to_vec()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Find `item` in the keyed list at `path`, looking in the environment before the base config
+///
+/// Returns the full path of the list it was found in and its index there
+fn find_item(
+    document: &Value,
+    environment_prefix: &[String],
+    path: &[String],
+    item: &Value,
+) -> Result<(Vec<String>, usize)> {
+    let position = |prefix: &[String]| {
+        let path = [prefix, path].concat();
+        lookup(document, &path)
+            .and_then(Value::as_sequence)
+            .and_then(|items| items.iter().position(|i| same_item(i, item)))
+            .map(|index| (path, index))
+    };
+
+    (!environment_prefix.is_empty())
+        .then(|| position(environment_prefix))
+        .flatten()
+        .or_else(|| position(&[]))
+        .ok_or_else(|| ConfigError::EntryNotFound(item_name(item)))
+}
+
+pub fn item_name(item: &Value) -> String {
+    item.get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("<unnamed>")
+        .to_string()
+}
+
+pub fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |value, key| value.get(key.as_str()))
+}
+
+pub fn lookup_mut<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    path.iter()
+        .try_fold(value, |value, key| value.get_mut(key.as_str()))
+}
+
+/// Get the value at `path`, creating mappings along the way if needed
+pub fn lookup_or_insert<'a>(value: &'a mut Value, path: &[String]) -> &'a mut Value {
+    path.iter().fold(value, |value, key| {
+        if !value.is_mapping() {
+            *value = Value::Mapping(Mapping::new());
+        }
+        let Value::Mapping(map) = value else {
+            unreachable!()
+        };
+        map.entry(Value::String(key.clone())).or_insert(Value::Null)
+    })
+}
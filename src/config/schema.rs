@@ -0,0 +1,147 @@
+use super::error::{ConfigError, Result};
+use super::types::{FerriteConfig, Side};
+use schemars::{JsonSchema, json_schema, schema_for};
+
+/// Schema of `libium::config::structs::Mod`, with the keys ferrite adds to it
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "Mod")]
+pub struct ModSchema {
+    name: String,
+    identifier: ModIdentifierSchema,
+    slug: Option<String>,
+    /// Groups the mod belongs to, selected with `--tag`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Where the mod is needed, instead of what its platform says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    side: Option<Side>,
+}
+
+/// Schema of `libium::config::structs::ModIdentifier`
+///
+/// In YAML the variant is written as a tag, e.g. `!ModrinthProject AANobbMI`, or with the
+/// version the mod is pinned to, e.g. `!ModrinthProject [AANobbMI, Yp8wLY1P]`
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "ModIdentifier")]
+pub enum ModIdentifierSchema {
+    /// Modrinth project ID, and the ID of the version it is pinned to
+    ModrinthProject(Pinnable<String, String>),
+    /// CurseForge project ID, and the ID of the file it is pinned to
+    CurseForgeProject(Pinnable<i32, i32>),
+    /// GitHub repository owner and name, and the release it is pinned to
+    GitHubRepository(Pinnable<(String, String), String>),
+}
+
+/// The project a `ModIdentifier` refers to, alone or next to the version it is pinned to
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[schemars(inline)]
+pub enum Pinnable<T, V> {
+    Latest(T),
+    Pinned(T, Option<V>),
+}
+
+/// Schema of `libium::config::structs::ModLoader`
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "ModLoader")]
+pub enum ModLoaderSchema {
+    Quilt,
+    Fabric,
+    Forge,
+    NeoForge,
+    Velocity,
+}
+
+/// Generate the JSON Schema of ferrite.yaml
+pub fn schema() -> Result<String> {
+    let mut schema = schema_for!(FerriteConfig);
+
+    // These are resolved before the config is deserialized, so they are not part of the type
+    if let Some(properties) = schema
+        .get_mut("properties")
+        .and_then(|properties| properties.as_object_mut())
+    {
+        properties.insert(
+            "include".to_string(),
+            json_schema!({
+                "description": "Files to inherit mods, overrides and disabled mods from, relative to this file",
+                "type": "array",
+                "items": { "type": "string" }
+            })
+            .to_value(),
+        );
+        properties.insert(
+            "environments".to_string(),
+            json_schema!({
+                "description": "Named partial configs merged over this one when selected with `--env`",
+                "type": "object",
+                "additionalProperties": { "type": "object" }
+            })
+            .to_value(),
+        );
+    }
+
+    serde_json::to_string_pretty(&schema).map_err(|e| ConfigError::Serialize(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::schema;
+    use crate::config::{Format, ModConfig};
+    use libium::config::structs::{Mod, ModIdentifier};
+    use serde_json::{Value, json};
+
+    /// Write a mod with `identifier` like a JSON config does, check it against the schema of
+    /// `ferium.mods` entries and read it back
+    fn round_trip(identifier: ModIdentifier) -> ModIdentifier {
+        let schema: Value = serde_json::from_str(&schema().unwrap()).unwrap();
+        let mod_schema = json!({ "$defs": schema["$defs"], "$ref": "#/$defs/Mod" });
+
+        let mod_ = Mod::new("Sodium".to_string(), identifier, vec![], false);
+        let entry = serde_norway::to_value(ModConfig::from(mod_)).unwrap();
+        let mut document = serde_norway::Value::Null;
+        document["ferium"]["mods"] = serde_norway::Value::Sequence(vec![entry]);
+
+        let written = Format::Json.render(&document).unwrap();
+        let instance: Value = serde_json::from_str(&written).unwrap();
+        if let Err(error) = jsonschema::validate(&mod_schema, &instance["ferium"]["mods"][0]) {
+            panic!("{written} does not match the schema: {error}");
+        }
+
+        let parsed = Format::Json.parse(&written).unwrap();
+        let entry: ModConfig = serde_norway::from_value(parsed["ferium"]["mods"][0].clone())
+            .expect("the written entry should deserialize");
+        entry.mod_.identifier
+    }
+
+    #[test]
+    fn pinned_modrinth_project() {
+        let identifier =
+            ModIdentifier::ModrinthProject("AANobbMI".to_string(), Some("Yp8wLY1P".to_string()));
+        assert_eq!(round_trip(identifier.clone()), identifier);
+    }
+
+    #[test]
+    fn pinned_curseforge_project() {
+        let identifier = ModIdentifier::CurseForgeProject(394468, Some(5683040));
+        assert_eq!(round_trip(identifier.clone()), identifier);
+    }
+
+    #[test]
+    fn unpinned_projects() {
+        for identifier in [
+            ModIdentifier::ModrinthProject("AANobbMI".to_string(), None),
+            ModIdentifier::CurseForgeProject(394468, None),
+            ModIdentifier::GitHubRepository(
+                ("gnembon".to_string(), "fabric-carpet".to_string()),
+                None,
+            ),
+        ] {
+            assert_eq!(round_trip(identifier.clone()), identifier);
+        }
+    }
+}
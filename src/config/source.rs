@@ -0,0 +1,77 @@
+use super::document::Document;
+use super::error::{ConfigError, Result};
+use super::include::Inherited;
+use super::patch::{Change, apply, diff, item_name};
+use serde_norway::Value;
+use std::path::Path;
+
+/// The document a config was loaded from
+///
+/// Keeps the file as it is on disk next to the config as it was loaded, so that writing
+/// only applies what a command changed, to the layer it belongs to
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    document: Document,
+    loaded: Value,
+    environment: Option<String>,
+    inherited: Inherited,
+}
+
+impl ConfigSource {
+    pub fn new(
+        document: Document,
+        loaded: Value,
+        environment: Option<String>,
+        inherited: Inherited,
+    ) -> Self {
+        Self {
+            document,
+            loaded,
+            environment,
+            inherited,
+        }
+    }
+
+    /// The included file a mod with `identifier` was inherited from
+    pub fn origin(&self, identifier: &Value) -> Option<&Path> {
+        self.inherited.mod_origin(identifier)
+    }
+
+    /// Apply the changes between the loaded config and `current` to the document
+    ///
+    /// Fails without changing anything if an inherited entry would be changed
+    pub fn update(&mut self, current: Value) -> Result<&Document> {
+        let changes = diff(&self.loaded, &current);
+
+        for change in &changes {
+            let inherited = match change {
+                Change::Delete(_, item) | Change::Replace(_, item) => item
+                    .get("identifier")
+                    .and_then(|identifier| self.inherited.mod_origin(identifier))
+                    .map(|origin| (item_name(item), origin)),
+                Change::Set(path, _) | Change::Remove(path) => match path.as_slice() {
+                    [ferium, overrides, key] if ferium == "ferium" && overrides == "overrides" => {
+                        self.inherited
+                            .override_origin(key)
+                            .map(|origin| (key.clone(), origin))
+                    }
+                    _ => None,
+                },
+                Change::Insert(..) => None,
+            };
+
+            if let Some((name, origin)) = inherited {
+                return Err(ConfigError::Inherited(name, origin.display().to_string()));
+            }
+        }
+
+        apply(&mut self.document, changes, self.environment.as_deref())?;
+        self.loaded = current;
+
+        Ok(&self.document)
+    }
+}
+
+pub fn to_value<T: serde::Serialize>(value: &T) -> Result<Value> {
+    serde_norway::to_value(value).map_err(|e| ConfigError::Serialize(e.to_string()))
+}
@@ -1,37 +1,205 @@
 use super::error::Result;
+use super::format::Format;
+use super::schema::{ModIdentifierSchema, ModLoaderSchema, ModSchema};
+use super::source::{ConfigSource, to_value};
+use crate::lock::lock_file;
+use colored::Colorize as _;
 use libium::config::structs::{Mod, ModIdentifier, ModLoader, Profile};
-use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, env, fs, io::Write};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
+use serde_norway::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    mem::discriminant,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct FerriteConfig {
+    /// Version of the config format
     pub version: i64,
+    /// Upgrade the mods after every command that changes them
     pub autoupdate: bool,
+    /// Directory the mods are installed to
     pub output_path: String,
+    /// Where the GitHub and CurseForge API keys and other secrets are read from
     pub key_store: KeyStoreConfig,
+    /// Entries in the key store to read secrets from, keyed by the secret's name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub key_store_entries: HashMap<String, String>,
+    /// Commit the config and ferrite.lock to git after the commands that change them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitConfig>,
     pub server: ServerConfig,
     pub ferium: FeriumConfig,
+    #[serde(skip)]
+    pub source: Option<ConfigSource>,
+    /// File the config was loaded from and is written to
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// Environment from `environments` that was merged over the base config
+    #[serde(skip)]
+    pub environment: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct ServerConfig {
+    /// Command that starts the server, `{}` is replaced with the executable
     pub wrapper: String,
+    /// Server jar or launch script
     pub executable: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema)]
 pub enum KeyStoreConfig {
+    /// Read `ferrite/<name>` from `pass`
     Pass,
+    /// Read upper-cased variables, after loading `.env`
     DotEnv,
+    /// Read upper-cased variables from the environment only
+    Env,
+    /// Run a command for every secret, `{}` is replaced with the entry
+    Command(String),
+    /// Read entries from a YAML file encrypted with a passphrase by age
+    EncryptedFile(String),
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct GitConfig {
+    /// What to do after every command that changes the config, unless `commands` sets it
+    #[serde(default)]
+    pub mode: GitMode,
+    /// What to do after specific commands, keyed by the command's name, e.g. `upgrade: Off`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, GitMode>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+pub enum GitMode {
+    /// Stage the config and ferrite.lock and commit them
+    #[default]
+    Commit,
+    /// Only stage the config and ferrite.lock
+    Stage,
+    /// Leave the repository alone
+    Off,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct FeriumConfig {
+    /// Minecraft versions to download mods for
     pub game_versions: Vec<String>,
+    /// Mod loaders to download mods for, the first one is the server's
+    #[schemars(with = "Vec<ModLoaderSchema>")]
     pub mod_loaders: Vec<ModLoader>,
+    /// Replacements for dependencies, keyed by the dependency's project ID
+    #[schemars(with = "HashMap<String, ModIdentifierSchema>")]
     pub overrides: HashMap<String, ModIdentifier>,
-    pub mods: Vec<Mod>,
-    pub disabled: Vec<Mod>,
+    #[schemars(with = "Vec<ModSchema>")]
+    pub mods: Vec<ModConfig>,
+    #[schemars(with = "Vec<ModSchema>")]
+    pub disabled: Vec<ModConfig>,
+}
+
+/// An entry of `ferium.mods` or `ferium.disabled`, libium's `Mod` with the keys only ferrite
+/// reads next to its own
+#[derive(Debug, Clone)]
+pub struct ModConfig {
+    pub mod_: Mod,
+    pub tags: Vec<String>,
+    /// Where the mod is needed, instead of what its platform says
+    pub side: Option<Side>,
+}
+
+/// The side of the game a mod is needed on
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub enum Side {
+    /// Only needed on clients, never installed on the server
+    Client,
+    /// Needed on the server, even if its platform says it is client-only
+    Server,
+}
+
+impl From<Mod> for ModConfig {
+    fn from(mod_: Mod) -> Self {
+        Self {
+            mod_,
+            tags: Vec::new(),
+            side: None,
+        }
+    }
+}
+
+impl ModConfig {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|other| other.eq_ignore_ascii_case(tag))
+    }
+}
+
+impl Deref for ModConfig {
+    type Target = Mod;
+
+    fn deref(&self) -> &Mod {
+        &self.mod_
+    }
+}
+
+impl DerefMut for ModConfig {
+    fn deref_mut(&mut self) -> &mut Mod {
+        &mut self.mod_
+    }
+}
+
+impl std::borrow::Borrow<Mod> for ModConfig {
+    fn borrow(&self) -> &Mod {
+        &self.mod_
+    }
+}
+
+// The identifier is a YAML tag, which `#[serde(flatten)]` cannot buffer, so ferrite's keys are
+// split off the entry's value before the rest is handed to libium
+impl<'de> Deserialize<'de> for ModConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let mut value = Value::deserialize(deserializer)?;
+        let mut take = |key: &str| value.as_mapping_mut().and_then(|entry| entry.remove(key));
+        let tags = take("tags")
+            .map(serde_norway::from_value)
+            .transpose()
+            .map_err(de::Error::custom)?
+            .unwrap_or_default();
+        let side = take("side")
+            .map(serde_norway::from_value)
+            .transpose()
+            .map_err(de::Error::custom)?;
+
+        Ok(Self {
+            mod_: serde_norway::from_value(value).map_err(de::Error::custom)?,
+            tags,
+            side,
+        })
+    }
+}
+
+impl Serialize for ModConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut value = serde_norway::to_value(&self.mod_).map_err(ser::Error::custom)?;
+        if let Some(entry) = value.as_mapping_mut() {
+            if !self.tags.is_empty() {
+                let tags = serde_norway::to_value(&self.tags).map_err(ser::Error::custom)?;
+                entry.insert("tags".into(), tags);
+            }
+            if let Some(side) = self.side {
+                let side = serde_norway::to_value(side).map_err(ser::Error::custom)?;
+                entry.insert("side".into(), side);
+            }
+        }
+
+        value.serialize(serializer)
+    }
 }
 
 impl FerriteConfig {
@@ -51,6 +219,8 @@ impl FerriteConfig {
             autoupdate: true,
             output_path,
             key_store: KeyStoreConfig::DotEnv,
+            key_store_entries: HashMap::new(),
+            git: None,
             server: ServerConfig {
                 wrapper,
                 executable,
@@ -62,40 +232,123 @@ impl FerriteConfig {
                 mods: vec![],
                 disabled: vec![],
             },
+            source: None,
+            path: PathBuf::from(super::CONFIG_FILE),
+            environment: None,
         }
     }
 
-    pub fn write_config(&self) -> Result<()> {
-        let serialized = serde_norway::to_string(self)?;
+    /// Write the config to its file
+    ///
+    /// A loaded YAML config only has the values that changed edited in place, keeping the rest
+    /// of the file as it was. TOML and JSON configs are serialized as a whole, and so is YAML
+    /// when a change can not be made in place, with a warning that its comments are lost.
+    pub fn write_config(&mut self) -> Result<()> {
+        let format = Format::of(&self.path);
+        let current = to_value(self)?;
+        let contents = match &mut self.source {
+            Some(source) => {
+                let document = source.update(current)?;
+                match document.text() {
+                    Some(text) => text,
+                    None => {
+                        if format == Format::Yaml {
+                            println!(
+                                "{}",
+                                format!(
+                                    "Warning: could not edit {} in place, it was rewritten without its comments and formatting",
+                                    self.path.display()
+                                )
+                                .yellow()
+                            );
+                        }
+                        format.render(document.value())?
+                    }
+                }
+            }
+            None => format.render(&current)?,
+        };
 
-        let mut file = fs::File::create("ferrite.yaml")?;
-        file.write_all(
-            "# https://github.com/septechx/ferrite/blob/master/schema/ferrite.yaml\n".as_bytes(),
-        )?;
-        file.write_all(serialized.as_bytes())?;
+        fs::write(&self.path, contents)?;
 
         Ok(())
     }
 
-    pub fn update(&mut self, profile: Profile) {
-        self.ferium.mods = profile.mods;
-        self.ferium.disabled = profile.disabled;
-        if let Err(e) = self.write_config() {
-            eprintln!("Error writing config: {}", e);
+    /// The directory of the config file, which relative paths in it are resolved against
+    pub fn dir(&self) -> &Path {
+        super::config_dir(&self.path)
+    }
+
+    /// The directory the mods are installed to
+    pub fn output_dir(&self) -> PathBuf {
+        self.dir().join(&self.output_path)
+    }
+
+    /// The lockfile of the config's environment
+    pub fn lock_path(&self) -> PathBuf {
+        self.dir().join(lock_file(self.environment.as_deref()))
+    }
+
+    /// The included file `mod_` was inherited from, if it is not defined in ferrite.yaml itself
+    pub fn origin(&self, mod_: &Mod) -> Option<&Path> {
+        let identifier = to_value(&mod_.identifier).ok()?;
+        self.source.as_ref()?.origin(&identifier)
+    }
+
+    /// Take the mods and disabled mods from `profile` and write the config
+    ///
+    /// Mods keep the tags and side they had, also when they moved between the two lists
+    pub fn update(&mut self, profile: &Profile) -> Result<()> {
+        let mods = profile.mods.iter().map(|mod_| self.entry(mod_)).collect();
+        let disabled = profile
+            .disabled
+            .iter()
+            .map(|mod_| self.entry(mod_))
+            .collect();
+        self.ferium.mods = mods;
+        self.ferium.disabled = disabled;
+        self.write_config()
+    }
+
+    /// `mod_` with the settings its project has in this config, regardless of the pinned version
+    fn entry(&self, mod_: &Mod) -> ModConfig {
+        let existing = self
+            .ferium
+            .mods
+            .iter()
+            .chain(&self.ferium.disabled)
+            .find(|entry| {
+                discriminant(&entry.identifier) == discriminant(&mod_.identifier)
+                    && entry.identifier.display_name() == mod_.identifier.display_name()
+            });
+
+        ModConfig {
+            mod_: mod_.clone(),
+            tags: existing.map(|entry| entry.tags.clone()).unwrap_or_default(),
+            side: existing.and_then(|entry| entry.side),
         }
     }
 }
 
 impl From<FerriteConfig> for Profile {
     fn from(config: FerriteConfig) -> Self {
-        let current_dir = env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
         Self::new_complete(
             String::from("ferrite"),
-            current_dir.join(&config.output_path),
+            config.output_dir(),
             config.ferium.game_versions,
             config.ferium.mod_loaders,
-            config.ferium.mods,
-            config.ferium.disabled,
+            config
+                .ferium
+                .mods
+                .into_iter()
+                .map(|entry| entry.mod_)
+                .collect(),
+            config
+                .ferium
+                .disabled
+                .into_iter()
+                .map(|entry| entry.mod_)
+                .collect(),
         )
     }
 }
@@ -1,4 +1,61 @@
-const LATEST_CONFIG_VERSION: i64 = 4;
+use super::error::{ConfigError, Result};
+use super::format::Format;
+
+pub const LATEST_CONFIG_VERSION: i64 = 4;
+
+/// The result of upgrading a config to the latest version
+pub struct Migration {
+    pub from: i64,
+    pub to: i64,
+    pub content: String,
+}
+
+/// Upgrade `content` in `format` to the latest config version
+///
+/// Fails for configs written by a newer version of ferrite, instead of guessing at them
+pub fn migrate(content: &str, format: Format) -> Result<Migration> {
+    if format != Format::Yaml {
+        // Migrations work on YAML, so other formats are converted to it and back
+        let yaml = serde_norway::to_string(&format.parse(content)?)?;
+        let migration = migrate(&yaml, Format::Yaml)?;
+        let content = if migration.to > migration.from {
+            format.render(&Format::Yaml.parse(&migration.content)?)?
+        } else {
+            content.to_string()
+        };
+        return Ok(Migration {
+            content,
+            ..migration
+        });
+    }
+
+    let from = detect_config_version(content);
+    if from > LATEST_CONFIG_VERSION {
+        return Err(ConfigError::UnsupportedVersion(from, LATEST_CONFIG_VERSION));
+    }
+
+    let mut version = from;
+    let mut content = content.to_string();
+    while needs_upgrade(version)
+        && let Some(upgraded) = upgrade_config(&content, version)?
+    {
+        let upgraded_version = detect_config_version(&upgraded);
+        if upgraded_version <= version {
+            return Err(ConfigError::Migration(
+                version + 1,
+                format!("the upgraded config still has version {upgraded_version}"),
+            ));
+        }
+        content = upgraded;
+        version = upgraded_version;
+    }
+
+    Ok(Migration {
+        from,
+        to: version,
+        content,
+    })
+}
 
 pub fn detect_config_version(content: &str) -> i64 {
     serde_norway::from_str::<serde_norway::Value>(content)
@@ -7,11 +64,11 @@ pub fn detect_config_version(content: &str) -> i64 {
         .unwrap_or(0)
 }
 
-pub fn upgrade_config(content: &str, from_version: i64) -> Option<String> {
+pub fn upgrade_config(content: &str, from_version: i64) -> Result<Option<String>> {
     match from_version {
-        v if v < 3 => Some(upgrade_config_to_v3(content)),
-        3 => Some(upgrade_config_to_v4(content)),
-        _ => None,
+        v if v < 3 => upgrade_config_to_v3(content).map(Some),
+        3 => upgrade_config_to_v4(content).map(Some),
+        _ => Ok(None),
     }
 }
 
@@ -19,12 +76,16 @@ pub fn needs_upgrade(version: i64) -> bool {
     version < LATEST_CONFIG_VERSION
 }
 
-fn parse_yaml(content: &str) -> serde_norway::Value {
-    serde_norway::from_str(content).unwrap_or(serde_norway::Value::Null)
+fn parse_yaml(content: &str, version: i64) -> Result<serde_norway::Value> {
+    serde_norway::from_str(content).map_err(|e| ConfigError::Migration(version, e.to_string()))
+}
+
+fn serialize_yaml(value: &serde_norway::Value, version: i64) -> Result<String> {
+    serde_norway::to_string(value).map_err(|e| ConfigError::Migration(version, e.to_string()))
 }
 
-fn upgrade_config_to_v3(content: &str) -> String {
-    let yaml = parse_yaml(content);
+fn upgrade_config_to_v3(content: &str) -> Result<String> {
+    let yaml = parse_yaml(content, 3)?;
 
     fn convert_identifier_to_tagged(value: &serde_norway::Value) -> serde_norway::Value {
         match value {
@@ -158,15 +219,17 @@ fn upgrade_config_to_v3(content: &str) -> String {
             serde_norway::Value::Number(serde_norway::Number::from(3)),
         );
 
-        serde_norway::to_string(&serde_norway::Value::Mapping(new_root))
-            .unwrap_or_else(|_| content.to_string())
+        serialize_yaml(&serde_norway::Value::Mapping(new_root), 3)
     } else {
-        content.to_string()
+        Err(ConfigError::Migration(
+            3,
+            "the config is not a mapping".to_string(),
+        ))
     }
 }
 
-fn upgrade_config_to_v4(content: &str) -> String {
-    let yaml = parse_yaml(content);
+fn upgrade_config_to_v4(content: &str) -> Result<String> {
+    let yaml = parse_yaml(content, 4)?;
 
     if let serde_norway::Value::Mapping(root) = &yaml {
         let mut new_root = root.clone();
@@ -196,9 +259,11 @@ fn upgrade_config_to_v4(content: &str) -> String {
             serde_norway::Value::Number(serde_norway::Number::from(4)),
         );
 
-        serde_norway::to_string(&serde_norway::Value::Mapping(new_root))
-            .unwrap_or_else(|_| content.to_string())
+        serialize_yaml(&serde_norway::Value::Mapping(new_root), 4)
     } else {
-        content.to_string()
+        Err(ConfigError::Migration(
+            4,
+            "the config is not a mapping".to_string(),
+        ))
     }
 }
@@ -0,0 +1,123 @@
+use super::types::FerriteConfig;
+use crate::git::COMMANDS;
+use crate::lock::Lockfile;
+use crate::mods::mod_matches;
+use crate::tree::node_id;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// Check `config` for mistakes that deserializing it does not catch
+///
+/// Overrides are checked against the dependencies recorded in `lockfile`, if there is one
+pub fn validate(config: &FerriteConfig, lockfile: Option<&Lockfile>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    if config.ferium.game_versions.is_empty() {
+        issues.push(Issue::error("`ferium.game_versions` is empty"));
+    }
+    if config.ferium.mod_loaders.is_empty() {
+        issues.push(Issue::error("`ferium.mod_loaders` is empty"));
+    }
+    if !config.server.wrapper.contains("{}") {
+        issues.push(Issue::error(
+            "`server.wrapper` does not contain `{}`, so the executable is never started",
+        ));
+    }
+    if config.output_path.trim().is_empty() {
+        issues.push(Issue::error("`output_path` is empty"));
+    }
+
+    let all_mods = config
+        .ferium
+        .mods
+        .iter()
+        .map(|mod_| (mod_, "mods"))
+        .chain(config.ferium.disabled.iter().map(|mod_| (mod_, "disabled")))
+        .collect::<Vec<_>>();
+    for (i, (mod_, list)) in all_mods.iter().enumerate() {
+        for (other, other_list) in &all_mods[..i] {
+            // The same project is a duplicate whether or not either entry is pinned
+            if node_id(&other.identifier) == node_id(&mod_.identifier) {
+                issues.push(Issue::error(format!(
+                    "{} is listed in both `ferium.{other_list}` ({}) and `ferium.{list}` ({})",
+                    mod_.identifier.display_name(),
+                    other.name,
+                    mod_.name
+                )));
+            } else if other.name.eq_ignore_ascii_case(&mod_.name) {
+                issues.push(Issue::warning(format!(
+                    "Two mods are named '{}', commands that take names will pick the first one",
+                    mod_.name
+                )));
+            }
+        }
+    }
+
+    for (key, identifier) in &config.ferium.overrides {
+        if identifier.display_name().trim().is_empty() {
+            issues.push(Issue::error(format!("The override for '{key}' is empty")));
+        }
+    }
+
+    if let Some(git) = &config.git {
+        for command in git.commands.keys() {
+            if !COMMANDS.contains(&command.as_str()) {
+                issues.push(Issue::warning(format!(
+                    "`git.commands.{command}` is not a command that changes the config, expected one of {}",
+                    COMMANDS.join(", ")
+                )));
+            }
+        }
+    }
+
+    match lockfile {
+        Some(lockfile) => {
+            // Dependencies are locked under the identifier they were overridden with
+            for (key, identifier) in &config.ferium.overrides {
+                let used = config.ferium.mods.iter().any(|mod_| mod_matches(mod_, key))
+                    || lockfile.mods.iter().any(|locked| {
+                        locked.identifier.display_name() == *key || locked.identifier == *identifier
+                    });
+                if !used {
+                    issues.push(Issue::warning(format!(
+                        "The override for '{key}' does not match any mod or dependency"
+                    )));
+                }
+            }
+        }
+        None if !config.ferium.overrides.is_empty() => {
+            issues.push(Issue::warning(
+                "Overrides were not checked, run `ferrite upgrade` to create ferrite.lock",
+            ));
+        }
+        None => {}
+    }
+
+    issues
+}
@@ -0,0 +1,439 @@
+use crate::config::FerriteConfig;
+use crate::lock::{Lockfile, Platform};
+use crate::mods::pinned_version;
+use colored::Colorize as _;
+use libium::config::structs::{Mod, ModIdentifier};
+use serde::Serialize;
+use std::{collections::BTreeSet, fs, io, path::Path};
+
+/// A project on one of the platforms, with the version it is pinned to if any
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Project {
+    pub platform: Platform,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl From<&ModIdentifier> for Project {
+    fn from(identifier: &ModIdentifier) -> Self {
+        Self {
+            platform: identifier.into(),
+            id: identifier.display_name(),
+            version: pinned_version(identifier),
+        }
+    }
+}
+
+impl Project {
+    /// Whether both refer to the same project, regardless of the version
+    fn same(&self, other: &Self) -> bool {
+        self.platform == other.platform && self.id == other.id
+    }
+
+    fn label(&self) -> String {
+        let platform = match self.platform {
+            Platform::Modrinth => "MR".green(),
+            Platform::CurseForge => "CF".red(),
+            Platform::GitHub => "GH".purple(),
+        };
+        format!("{platform} {:8}", self.id.dimmed())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub project: Project,
+}
+
+impl From<&Mod> for ModEntry {
+    fn from(mod_: &Mod) -> Self {
+        Self {
+            name: mod_.name.clone(),
+            project: (&mod_.identifier).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverrideEntry {
+    /// The dependency that is replaced
+    pub dependency: String,
+    #[serde(flatten)]
+    pub project: Project,
+}
+
+/// Something that is in both configs, but differs between them
+#[derive(Debug, Serialize)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+/// The differences of one part of the configs, from this config to the other one
+#[derive(Debug, Serialize)]
+pub struct Changes<T> {
+    /// Only in the other config
+    pub added: Vec<T>,
+    /// Only in this config
+    pub removed: Vec<T>,
+    /// In both configs, but different
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<Change<T>>,
+}
+
+impl<T: Clone> Changes<T> {
+    /// Compare `this` with `other`, matching items with `same` and comparing matches with `eq`
+    fn between(
+        this: &[T],
+        other: &[T],
+        same: impl Fn(&T, &T) -> bool,
+        eq: impl Fn(&T, &T) -> bool,
+    ) -> Self {
+        let removed = this
+            .iter()
+            .filter(|item| !other.iter().any(|o| same(item, o)))
+            .cloned()
+            .collect();
+        let added = other
+            .iter()
+            .filter(|item| !this.iter().any(|t| same(item, t)))
+            .cloned()
+            .collect();
+        let changed = this
+            .iter()
+            .filter_map(|item| {
+                other
+                    .iter()
+                    .find(|o| same(item, o) && !eq(item, o))
+                    .map(|o| Change {
+                        from: item.clone(),
+                        to: o.clone(),
+                    })
+            })
+            .collect();
+
+        Self {
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The differences between two configs
+#[derive(Debug, Serialize)]
+pub struct ConfigDiff {
+    pub game_versions: Changes<String>,
+    pub mod_loaders: Changes<String>,
+    pub mods: Changes<ModEntry>,
+    pub overrides: Changes<OverrideEntry>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.game_versions.is_empty()
+            && self.mod_loaders.is_empty()
+            && self.mods.is_empty()
+            && self.overrides.is_empty()
+    }
+}
+
+/// Compare the mods, overrides, mod loaders and game versions of `this` with `other`
+///
+/// Mods and overrides are matched by their project, a different pinned version is a change
+pub fn compare(this: &FerriteConfig, other: &FerriteConfig) -> ConfigDiff {
+    let loaders = |config: &FerriteConfig| {
+        config
+            .ferium
+            .mod_loaders
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    };
+    let mods = |config: &FerriteConfig| {
+        config
+            .ferium
+            .mods
+            .iter()
+            .map(|mod_| ModEntry::from(&mod_.mod_))
+            .collect::<Vec<_>>()
+    };
+    let overrides = |config: &FerriteConfig| {
+        let mut overrides = config
+            .ferium
+            .overrides
+            .iter()
+            .map(|(dependency, identifier)| OverrideEntry {
+                dependency: dependency.clone(),
+                project: identifier.into(),
+            })
+            .collect::<Vec<_>>();
+        overrides.sort_by(|a, b| a.dependency.cmp(&b.dependency));
+        overrides
+    };
+
+    ConfigDiff {
+        game_versions: Changes::between(
+            &this.ferium.game_versions,
+            &other.ferium.game_versions,
+            |a, b| a == b,
+            |a, b| a == b,
+        ),
+        mod_loaders: Changes::between(
+            &loaders(this),
+            &loaders(other),
+            |a, b| a == b,
+            |a, b| a == b,
+        ),
+        mods: Changes::between(
+            &mods(this),
+            &mods(other),
+            |a, b| a.project.same(&b.project),
+            |a, b| a.project == b.project,
+        ),
+        overrides: Changes::between(
+            &overrides(this),
+            &overrides(other),
+            |a, b| a.dependency == b.dependency,
+            |a, b| a == b,
+        ),
+    }
+}
+
+/// A file in ferrite.lock
+#[derive(Debug, Serialize)]
+pub struct LockedFile {
+    pub name: String,
+    pub filename: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dependency: bool,
+}
+
+/// The differences between the config, ferrite.lock and the jars in `output_path`
+#[derive(Debug, Serialize)]
+pub struct InstalledDiff {
+    /// Locked files that are not in `output_path`
+    pub missing: Vec<LockedFile>,
+    /// Jars in `output_path` that are neither locked nor in `user/`
+    pub unmanaged: Vec<String>,
+    /// Mods in the config that have not been resolved into ferrite.lock yet
+    pub unlocked: Vec<ModEntry>,
+    /// Mods in ferrite.lock that are no longer in the config
+    pub stale: Vec<ModEntry>,
+}
+
+impl InstalledDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.unmanaged.is_empty()
+            && self.unlocked.is_empty()
+            && self.stale.is_empty()
+    }
+}
+
+/// Compare `ferium.mods` and their dependencies, as locked in `lockfile`, with the jars in
+/// `output_dir`
+pub fn installed(
+    config: &FerriteConfig,
+    lockfile: &Lockfile,
+    output_dir: &Path,
+) -> io::Result<InstalledDiff> {
+    let present = jars(output_dir)?;
+    let user = jars(&output_dir.join("user"))?;
+
+    let missing = lockfile
+        .mods
+        .iter()
+        .filter(|locked| !present.contains(&locked.filename))
+        .map(|locked| LockedFile {
+            name: locked.name.clone(),
+            filename: locked.filename.clone(),
+            dependency: locked.dependency,
+        })
+        .collect();
+    let unmanaged = present
+        .iter()
+        .filter(|jar| !user.contains(*jar))
+        .filter(|jar| !lockfile.mods.iter().any(|locked| locked.filename == **jar))
+        .cloned()
+        .collect();
+
+    let locked = lockfile
+        .mods
+        .iter()
+        .filter(|locked| !locked.dependency)
+        .collect::<Vec<_>>();
+    let unlocked = config
+        .ferium
+        .mods
+        .iter()
+        .filter(|mod_| {
+            !locked
+                .iter()
+                .any(|locked| locked.identifier == mod_.identifier)
+                && !lockfile
+                    .skipped
+                    .iter()
+                    .any(|skipped| skipped.identifier == mod_.identifier)
+        })
+        .map(|mod_| ModEntry::from(&mod_.mod_))
+        .collect();
+    let stale = locked
+        .iter()
+        .filter(|locked| {
+            !config
+                .ferium
+                .mods
+                .iter()
+                .any(|mod_| mod_.identifier == locked.identifier)
+        })
+        .map(|locked| ModEntry {
+            name: locked.name.clone(),
+            project: (&locked.identifier).into(),
+        })
+        .collect();
+
+    Ok(InstalledDiff {
+        missing,
+        unmanaged,
+        unlocked,
+        stale,
+    })
+}
+
+/// Names of the jars in `directory`, empty if it does not exist
+fn jars(directory: &Path) -> io::Result<BTreeSet<String>> {
+    if !directory.is_dir() {
+        return Ok(BTreeSet::new());
+    }
+
+    let mut jars = BTreeSet::new();
+    for file in fs::read_dir(directory)? {
+        let file = file?;
+        let path = file.path();
+        if path.is_file()
+            && path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jar"))
+        {
+            jars.insert(file.file_name().to_string_lossy().to_string());
+        }
+    }
+
+    Ok(jars)
+}
+
+fn print_section<T: Clone>(title: &str, changes: &Changes<T>, show: impl Fn(&T) -> String) {
+    if changes.is_empty() {
+        return;
+    }
+
+    println!("{}", title.bold());
+    for item in &changes.removed {
+        println!("  {} {}", "-".red(), show(item));
+    }
+    for item in &changes.added {
+        println!("  {} {}", "+".green(), show(item));
+    }
+    for change in &changes.changed {
+        println!(
+            "  {} {} → {}",
+            "~".yellow(),
+            show(&change.from),
+            show(&change.to)
+        );
+    }
+}
+
+fn show_project(project: &Project) -> String {
+    match &project.version {
+        Some(version) => format!("{} {}", project.label(), format!("@{version}").cyan()),
+        None => project.label(),
+    }
+}
+
+/// Print `diff` with `-` for what is only in this config and `+` for what is only in the other
+pub fn print_config_diff(diff: &ConfigDiff) {
+    if diff.is_empty() {
+        println!("{}", "The configs have the same mods and versions".dimmed());
+        return;
+    }
+
+    print_section("Game versions", &diff.game_versions, |version| {
+        version.green().to_string()
+    });
+    print_section("Mod loaders", &diff.mod_loaders, |loader| {
+        loader.purple().to_string()
+    });
+    print_section("Mods", &diff.mods, |mod_| {
+        format!("{}  {}", show_project(&mod_.project), mod_.name.bold())
+    });
+    print_section("Overrides", &diff.overrides, |override_| {
+        format!(
+            "{} → {}",
+            override_.dependency.bold(),
+            show_project(&override_.project)
+        )
+    });
+}
+
+pub fn print_installed_diff(diff: &InstalledDiff, output_path: &str) {
+    if diff.is_empty() {
+        println!(
+            "{}",
+            format!("{output_path} matches the config and ferrite.lock").dimmed()
+        );
+        return;
+    }
+
+    if !diff.missing.is_empty() {
+        println!("{}", format!("Missing from {output_path}").bold());
+        for locked in &diff.missing {
+            println!(
+                "  {} {}  {}",
+                "-".red(),
+                locked.filename,
+                if locked.dependency {
+                    format!("(dependency: {})", locked.name).dimmed()
+                } else {
+                    format!("({})", locked.name).dimmed()
+                }
+            );
+        }
+    }
+    if !diff.unmanaged.is_empty() {
+        println!("{}", "Not managed by ferrite".bold());
+        for jar in &diff.unmanaged {
+            println!("  {} {}", "+".green(), jar);
+        }
+    }
+    if !diff.unlocked.is_empty() {
+        println!("{}", "Not resolved yet, run `ferrite upgrade`".bold());
+        for mod_ in &diff.unlocked {
+            println!(
+                "  {} {}  {}",
+                "!".yellow(),
+                show_project(&mod_.project),
+                mod_.name.bold()
+            );
+        }
+    }
+    if !diff.stale.is_empty() {
+        println!("{}", "Locked but no longer in the config".bold());
+        for mod_ in &diff.stale {
+            println!(
+                "  {} {}  {}",
+                "!".yellow(),
+                show_project(&mod_.project),
+                mod_.name.bold()
+            );
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::config::{FerriteConfig, GitMode};
+use colored::Colorize as _;
+use libium::config::structs::Mod;
+use std::{
+    borrow::Borrow,
+    fs, io,
+    path::Path,
+    process::{Command, Output},
+};
+use thiserror::Error;
+
+/// Commands that can be configured in `git.commands`
+pub const COMMANDS: [&str; 15] = [
+    "add",
+    "remove",
+    "disable",
+    "enable",
+    "pin",
+    "unpin",
+    "tag",
+    "untag",
+    "override",
+    "script",
+    "upgrade",
+    "prune",
+    "update-version",
+    "config",
+    "undo",
+];
+
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("`git {0}` failed: {1}")]
+    Command(String, String),
+
+    #[error("Refusing to commit, other files are already staged: {0}")]
+    UnrelatedStaged(String),
+}
+
+pub type Result<T> = std::result::Result<T, GitError>;
+
+/// Stage the config and its lockfile after `command` changed them, and commit them with
+/// `message`, as configured in the config's `git` section
+pub fn commit(config: &FerriteConfig, command: &str, message: &str) -> Result<()> {
+    let Some(settings) = &config.git else {
+        return Ok(());
+    };
+    let mode = settings
+        .commands
+        .get(command)
+        .copied()
+        .unwrap_or(settings.mode);
+    if mode == GitMode::Off {
+        return Ok(());
+    }
+
+    let mut files = vec![config.path.clone()];
+    let lock_path = config.lock_path();
+    if fs::exists(&lock_path)? {
+        files.push(lock_path);
+    }
+
+    // Git runs in the config's directory, which is not necessarily the current one
+    let dir = config.dir();
+
+    // Committing whatever else is staged would sneak unrelated changes into the commit
+    let root = fs::canonicalize(run(dir, &["rev-parse", "--show-toplevel"])?.trim())?;
+    let ours = files
+        .iter()
+        .map(fs::canonicalize)
+        .collect::<io::Result<Vec<_>>>()?;
+    let unrelated = run(dir, &["diff", "--cached", "--name-only", "-z"])?
+        .split('\0')
+        .filter(|name| !name.is_empty() && !ours.contains(&root.join(name)))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if !unrelated.is_empty() {
+        return Err(GitError::UnrelatedStaged(unrelated.join(", ")));
+    }
+
+    let mut add = vec!["add", "--"];
+    add.extend(files.iter().filter_map(|file| file.to_str()));
+    run(dir, &add)?;
+
+    // Nothing to commit if the files are the same as in HEAD
+    if git(dir, &["diff", "--cached", "--quiet"])?.status.success() {
+        return Ok(());
+    }
+
+    match mode {
+        GitMode::Commit => {
+            run(dir, &["commit", "--quiet", "--message", message])?;
+            println!("{} Committed \"{}\"", "✓".green(), message);
+        }
+        GitMode::Stage => println!(
+            "{} Staged {}",
+            "✓".green(),
+            files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        GitMode::Off => {}
+    }
+
+    Ok(())
+}
+
+/// `command` without the value of a `config set`, which may be a secret
+pub fn redact(command: &str) -> String {
+    let words: Vec<&str> = command.split(' ').collect();
+    match words.windows(2).position(|pair| pair == ["config", "set"]) {
+        // Keep the key after `config set`
+        Some(index) => words[..words.len().min(index + 3)].join(" "),
+        None => command.to_string(),
+    }
+}
+
+/// Names of the mods in `before` that are not in `after`, for commit messages
+pub fn removed_mods(before: &[impl Borrow<Mod>], after: &[impl Borrow<Mod>]) -> String {
+    before
+        .iter()
+        .map(Borrow::borrow)
+        .filter(|mod_: &&Mod| {
+            !after
+                .iter()
+                .any(|other| other.borrow().identifier == mod_.identifier)
+        })
+        .map(|mod_| mod_.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<Output> {
+    Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|e| GitError::Command(args.join(" "), e.to_string()))
+}
+
+/// Run git with `args` in `dir`, returning its output if it succeeded
+fn run(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = git(dir, args)?;
+    if !output.status.success() {
+        return Err(GitError::Command(
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,33 @@
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to parse .ferrite/history: {0}")]
+    Parse(String),
+
+    #[error("Failed to serialize history entry: {0}")]
+    Serialize(String),
+
+    #[error("There is nothing to undo")]
+    NothingToUndo,
+
+    #[error("Cannot undo {0} changes, only {1} can be undone")]
+    TooFew(usize, usize),
+
+    #[error(
+        "{0} was changed after change #{1} was recorded, undoing it would discard those changes"
+    )]
+    Modified(String, usize),
+}
+
+impl From<serde_json::Error> for HistoryError {
+    fn from(e: serde_json::Error) -> Self {
+        HistoryError::Parse(e.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, HistoryError>;
@@ -0,0 +1,270 @@
+mod error;
+mod types;
+
+pub use error::{HistoryError, Result};
+pub use types::{Entry, FileChange};
+
+use crate::config::{FerriteConfig, unified_diff};
+use jiff::Timestamp;
+use std::{
+    collections::{BTreeSet, HashSet},
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Journal of the changes made by commands, one JSON entry per line, next to the config
+pub const HISTORY_FILE: &str = ".ferrite/history";
+
+/// Read every entry in the `.ferrite/history` of the config in `dir`, oldest first
+pub fn load_history(dir: &Path) -> Result<Vec<Entry>> {
+    let path = dir.join(HISTORY_FILE);
+    if !fs::exists(&path)? {
+        return Ok(Vec::new());
+    }
+
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Snapshot of the config, the lockfile and the installed files before a command changes them
+///
+/// Commands finish recording after the upgrade or prune that follows the config change, so the
+/// files it replaced are recorded too, and finish as well when that step fails, so the change is
+/// recorded either way. Undoing it restores the lockfile as it was at the start.
+pub struct Recorder {
+    dir: PathBuf,
+    config_path: PathBuf,
+    output_path: PathBuf,
+    lock_path: PathBuf,
+    config: String,
+    files: BTreeSet<String>,
+    lock: Option<String>,
+}
+
+impl Recorder {
+    pub fn start(config: &FerriteConfig) -> Result<Self> {
+        Self::new(
+            config.dir(),
+            &config.path,
+            &config.output_dir(),
+            &config.lock_path(),
+        )
+    }
+
+    fn new(dir: &Path, config_path: &Path, output_path: &Path, lock_path: &Path) -> Result<Self> {
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            config_path: config_path.to_path_buf(),
+            output_path: output_path.to_path_buf(),
+            lock_path: lock_path.to_path_buf(),
+            config: fs::read_to_string(config_path)?,
+            files: installed_files(output_path)?,
+            lock: read_optional(lock_path)?,
+        })
+    }
+
+    /// Append what the command changed to the history, if it changed anything
+    pub fn finish(self) -> Result<()> {
+        self.record(Vec::new())
+    }
+
+    fn record(self, undoes: Vec<usize>) -> Result<()> {
+        let config = fs::read_to_string(&self.config_path)?;
+        let installed = installed_files(&self.output_path)?;
+        let files = self
+            .files
+            .difference(&installed)
+            .cloned()
+            .map(FileChange::Removed)
+            .chain(
+                installed
+                    .difference(&self.files)
+                    .cloned()
+                    .map(FileChange::Added),
+            )
+            .collect::<Vec<_>>();
+
+        if config == self.config && files.is_empty() {
+            return Ok(());
+        }
+
+        let name = self
+            .config_path
+            .strip_prefix(&self.dir)
+            .unwrap_or(&self.config_path)
+            .display()
+            .to_string();
+        let entry = Entry {
+            id: load_history(&self.dir)?
+                .last()
+                .map_or(1, |entry| entry.id + 1),
+            timestamp: Timestamp::now(),
+            command: command_line(),
+            config: self.config_path,
+            output_path: self.output_path,
+            lock_path: self.lock_path,
+            diff: unified_diff(&self.config, &config, &name, &name),
+            checksum: checksum(&config),
+            before: self.config,
+            files,
+            lock: Some(self.lock),
+            undoes,
+        };
+
+        let line =
+            serde_json::to_string(&entry).map_err(|e| HistoryError::Serialize(e.to_string()))?;
+        let path = self.dir.join(HISTORY_FILE);
+        fs::create_dir_all(path.parent().unwrap_or(&self.dir))?;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{line}")?;
+
+        Ok(())
+    }
+}
+
+/// What `undo` restored
+pub struct Undone {
+    /// The changes that were undone, newest first
+    pub entries: Vec<Entry>,
+    /// Files that could not be restored because they are no longer in `.old`
+    pub missing: Vec<String>,
+}
+
+/// Undo the last `count` changes to the config in `dir` that have not been undone yet
+///
+/// The config and its lockfile are restored from the history, removed mod files are moved
+/// back from `.old` and added ones are moved there. The undo is recorded as a change itself.
+pub fn undo(dir: &Path, count: usize) -> Result<Undone> {
+    let history = load_history(dir)?;
+    let undone: HashSet<usize> = history
+        .iter()
+        .flat_map(|entry| entry.undoes.iter().copied())
+        .collect();
+    // Older entries have their paths relative to the config's directory
+    let entries: Vec<Entry> = history
+        .into_iter()
+        .rev()
+        .filter(|entry| entry.undoes.is_empty() && !undone.contains(&entry.id))
+        .take(count)
+        .map(|entry| Entry {
+            config: dir.join(&entry.config),
+            output_path: dir.join(&entry.output_path),
+            lock_path: dir.join(&entry.lock_path),
+            ..entry
+        })
+        .collect();
+
+    let (Some(newest), Some(oldest)) = (entries.first(), entries.last()) else {
+        return Err(HistoryError::NothingToUndo);
+    };
+    if entries.len() < count {
+        return Err(HistoryError::TooFew(count, entries.len()));
+    }
+
+    // Refuse to undo over edits that were not recorded, checking every step before changing
+    // anything so that an undo is never left half done
+    let mut current = read_optional(&newest.config)?.unwrap_or_default();
+    for entry in &entries {
+        if checksum(&current) != entry.checksum {
+            return Err(HistoryError::Modified(
+                entry.config.display().to_string(),
+                entry.id,
+            ));
+        }
+        current = entry.before.clone();
+    }
+
+    let recorder = Recorder::new(dir, &newest.config, &newest.output_path, &newest.lock_path)?;
+    let mut missing = Vec::new();
+    for entry in &entries {
+        missing.extend(restore_files(entry)?);
+        match &entry.lock {
+            Some(Some(lock)) => fs::write(&entry.lock_path, lock)?,
+            // The lockfile was created by the change or after it
+            Some(None) if fs::exists(&entry.lock_path)? => fs::remove_file(&entry.lock_path)?,
+            _ => {}
+        }
+    }
+    fs::write(&oldest.config, &oldest.before)?;
+    recorder.record(entries.iter().map(|entry| entry.id).collect())?;
+
+    Ok(Undone { entries, missing })
+}
+
+/// Reverse the file changes of `entry`, returning the files that could not be restored
+fn restore_files(entry: &Entry) -> Result<Vec<String>> {
+    let old = entry.output_path.join(".old");
+    let mut missing = Vec::new();
+
+    for change in &entry.files {
+        match change {
+            FileChange::Added(name) => {
+                let path = entry.output_path.join(name);
+                if path.is_file() {
+                    fs::create_dir_all(&old)?;
+                    fs::rename(path, old.join(name))?;
+                }
+            }
+            FileChange::Removed(name) => {
+                let path = old.join(name);
+                if path.is_file() {
+                    fs::rename(path, entry.output_path.join(name))?;
+                } else {
+                    missing.push(name.clone());
+                }
+            }
+        }
+    }
+
+    Ok(missing)
+}
+
+/// Names of the files in `directory`, ignoring partial downloads
+fn installed_files(directory: &Path) -> Result<BTreeSet<String>> {
+    if !directory.is_dir() {
+        return Ok(BTreeSet::new());
+    }
+
+    let mut files = BTreeSet::new();
+    for file in fs::read_dir(directory)? {
+        let file = file?;
+        let name = file.file_name().to_string_lossy().to_string();
+        if file.file_type()?.is_file() && !name.ends_with("part") {
+            files.insert(name);
+        }
+    }
+
+    Ok(files)
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    if !fs::exists(path)? {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(path)?))
+}
+
+fn checksum(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
+
+/// The command line ferrite was started with, quoting arguments that contain spaces
+fn command_line() -> String {
+    let args = env::args().skip(1).map(|arg| {
+        if arg.is_empty() || arg.contains(char::is_whitespace) {
+            format!("{arg:?}")
+        } else {
+            arg
+        }
+    });
+
+    std::iter::once("ferrite".to_string())
+        .chain(args)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,79 @@
+use crate::lock::LOCKFILE;
+use jiff::Timestamp;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::PathBuf;
+
+/// A change made by a command, as recorded in `.ferrite/history`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Entry {
+    /// Number of the change, counting from 1
+    pub id: usize,
+    pub timestamp: Timestamp,
+    /// Command line the change was made with
+    pub command: String,
+    /// Config file that was changed
+    pub config: PathBuf,
+    /// Directory the mods are installed to
+    pub output_path: PathBuf,
+    /// Unified diff of the config
+    pub diff: String,
+    /// The config as it was before the change
+    pub before: String,
+    /// BLAKE3 hash of the config after the change, to notice edits made since
+    pub checksum: String,
+    /// Files that were added to or removed from `output_path`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileChange>,
+    /// Lockfile of the environment the change was made in
+    #[serde(default = "default_lock_path")]
+    pub lock_path: PathBuf,
+    /// The lockfile as it was before the change, `Some(None)` if there was none
+    ///
+    /// Older entries only have it if the change rewrote the lockfile
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present"
+    )]
+    pub lock: Option<Option<String>>,
+    /// Changes this one undid, if it was made by `ferrite undo`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub undoes: Vec<usize>,
+}
+
+/// Entries recorded before environments had their own lockfiles all changed ferrite.lock
+fn default_lock_path() -> PathBuf {
+    PathBuf::from(LOCKFILE)
+}
+
+/// Read a field that is there as `Some`, even if it is `null`, which a missing field is not
+fn present<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Option<String>>, D::Error> {
+    Option::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    Added(String),
+    Removed(String),
+}
+
+impl Entry {
+    /// Number of lines added to and removed from the config
+    pub fn line_counts(&self) -> (usize, usize) {
+        let lines = self
+            .diff
+            .lines()
+            .filter(|line| !line.starts_with("+++") && !line.starts_with("---"));
+        lines.fold((0, 0), |(added, removed), line| {
+            if line.starts_with('+') {
+                (added + 1, removed)
+            } else if line.starts_with('-') {
+                (added, removed + 1)
+            } else {
+                (added, removed)
+            }
+        })
+    }
+}
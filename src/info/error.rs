@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum InfoError {
+    #[error("Modrinth API error: {0}")]
+    ModrinthApi(#[from] ferinth::Error),
+
+    #[error("CurseForge API error: {0}")]
+    CurseApi(#[from] furse::Error),
+
+    #[error("GitHub API error: {0}")]
+    GitHubApi(String),
+}
+
+pub type Result<T> = std::result::Result<T, InfoError>;
@@ -0,0 +1,254 @@
+mod error;
+
+pub use error::{InfoError, Result};
+
+use crate::config::FerriteConfig;
+use crate::lock::Lockfile;
+use crate::mods::{mod_matches, pinned_label};
+use crate::search::{compact, support};
+use colored::Colorize as _;
+use libium::{
+    CURSEFORGE_API, GITHUB_API, MODRINTH_API,
+    config::structs::{Mod, ModIdentifier, Profile},
+    iter_ext::IterExt as _,
+};
+
+/// What the platform says about a project
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub title: String,
+    pub description: String,
+    pub authors: Vec<String>,
+    pub license: Option<String>,
+    /// Whether the project is needed on the client, `None` if the platform does not say
+    pub client_side: Option<String>,
+    /// Whether the project is needed on the server, `None` if the platform does not say
+    pub server_side: Option<String>,
+    pub downloads: Option<u64>,
+    pub source: Option<String>,
+}
+
+/// Fetch the details of the project `identifier` refers to from its platform
+pub async fn project(identifier: &ModIdentifier) -> Result<Project> {
+    match identifier {
+        ModIdentifier::ModrinthProject(id, _) => {
+            let (project, members) = tokio::try_join!(
+                MODRINTH_API.project_get(id),
+                MODRINTH_API.team_list_project_members(id),
+            )?;
+
+            Ok(Project {
+                title: project.title,
+                description: project.description,
+                authors: members
+                    .into_iter()
+                    .map(|member| member.user.username)
+                    .collect(),
+                license: Some(project.license.id),
+                client_side: Some(support(project.client_side).to_string()),
+                server_side: Some(support(project.server_side).to_string()),
+                downloads: Some(project.downloads as u64),
+                source: project.source_url.map(|url| url.to_string()),
+            })
+        }
+        ModIdentifier::CurseForgeProject(id, _) => {
+            let project = CURSEFORGE_API.get_mod(*id).await?;
+
+            Ok(Project {
+                title: project.name,
+                description: project.summary,
+                authors: project
+                    .authors
+                    .into_iter()
+                    .map(|author| author.name)
+                    .collect(),
+                // CurseForge does not publish licenses or sides through its API
+                license: None,
+                client_side: None,
+                server_side: None,
+                downloads: Some(project.download_count as u64),
+                source: project
+                    .links
+                    .source_url
+                    .or(project.links.website_url)
+                    .map(|url| url.to_string()),
+            })
+        }
+        ModIdentifier::GitHubRepository((owner, repo), _) => {
+            let repository = GITHUB_API
+                .repos(owner, repo)
+                .get()
+                .await
+                .map_err(|e| InfoError::GitHubApi(e.to_string()))?;
+
+            Ok(Project {
+                title: repository
+                    .full_name
+                    .unwrap_or_else(|| format!("{owner}/{repo}")),
+                description: repository.description.unwrap_or_default(),
+                authors: repository
+                    .owner
+                    .map(|author| author.login)
+                    .into_iter()
+                    .collect(),
+                license: repository.license.map(|license| license.spdx_id),
+                client_side: None,
+                server_side: None,
+                downloads: None,
+                source: repository.html_url.map(|url| url.to_string()),
+            })
+        }
+    }
+}
+
+/// Print the project details of `mod_`, the file installed for it and the latest compatible
+/// version with the dependencies it pulls in
+pub async fn show(
+    config: &FerriteConfig,
+    mod_: &Mod,
+    disabled: bool,
+    lockfile: Option<&Lockfile>,
+) -> Result<()> {
+    let project = project(&mod_.identifier).await?;
+
+    println!(
+        "{}  {}{}",
+        project.title.bold(),
+        mod_.identifier.display_name().dimmed(),
+        if disabled {
+            format!("  {}", "(disabled)".yellow())
+        } else {
+            String::new()
+        }
+    );
+    if !project.description.is_empty() {
+        println!("{}", project.description);
+    }
+    println!();
+
+    field("Authors", project.authors.iter().display(", ").to_string());
+    if let Some(license) = &project.license {
+        field("License", license.clone());
+    }
+    if let (Some(server), Some(client)) = (&project.server_side, &project.client_side) {
+        field("Sides", format!("server {server}, client {client}"));
+    }
+    if let Some(downloads) = project.downloads {
+        field("Downloads", compact(downloads));
+    }
+    if let Some(source) = &project.source {
+        field("Source", source.clone());
+    }
+    println!();
+
+    let installed = lockfile.and_then(|lockfile| {
+        lockfile
+            .mods
+            .iter()
+            .find(|locked| !locked.dependency && locked.identifier == mod_.identifier)
+    });
+    let output_dir = config.output_dir();
+    match installed {
+        Some(locked) => {
+            let suffix = if disabled { ".disabled" } else { "" };
+            let present = output_dir
+                .join(format!("{}{suffix}", locked.filename))
+                .is_file();
+            field(
+                "Installed",
+                format!(
+                    "{}  {}  {}{}",
+                    locked.filename,
+                    locked
+                        .version
+                        .as_ref()
+                        .unwrap_or(&locked.version_id)
+                        .dimmed(),
+                    size::Size::from_bytes(locked.size)
+                        .format()
+                        .with_base(size::Base::Base10),
+                    if present {
+                        String::new()
+                    } else {
+                        format!("  {}", format!("(not in {})", config.output_path).yellow())
+                    }
+                ),
+            );
+        }
+        None => match lockfile.and_then(|lockfile| {
+            lockfile
+                .skipped
+                .iter()
+                .find(|skipped| skipped.identifier == mod_.identifier)
+        }) {
+            Some(skipped) => field("Installed", format!("skipped, {}", skipped.reason)),
+            None => field(
+                "Installed",
+                "not resolved yet, run `ferrite upgrade`"
+                    .yellow()
+                    .to_string(),
+            ),
+        },
+    }
+    if let Some(version) = pinned_label(&mod_.identifier, lockfile) {
+        field("Pinned to", version.cyan().to_string());
+    }
+    for (key, identifier) in &config.ferium.overrides {
+        // The override for `key` replaces this mod when another mod depends on it
+        if mod_matches(mod_, key) {
+            field(
+                "Overridden",
+                format!("by {}", identifier.display_name().bold()),
+            );
+        }
+    }
+
+    let profile: Profile = config.clone().into();
+    let latest = match mod_.fetch_download_file(profile.filters).await {
+        Ok(latest) => latest,
+        Err(err) => {
+            field("Latest", err.to_string().red().to_string());
+            return Ok(());
+        }
+    };
+    let filename = latest.filename();
+    let status = match installed {
+        Some(locked) if locked.filename == filename => "(installed)".green(),
+        Some(_) => "(update available)".yellow(),
+        None => "".normal(),
+    };
+    field("Latest", format!("{filename}  {status}"));
+
+    if latest.dependencies.is_empty() {
+        return Ok(());
+    }
+    println!("\n{}", "Dependencies".bold());
+    for dependency in &latest.dependencies {
+        let key = dependency.display_name();
+        let override_ = config.ferium.overrides.get(&key);
+        let identifier = override_.unwrap_or(dependency);
+        let name = lockfile
+            .and_then(|lockfile| {
+                lockfile
+                    .mods
+                    .iter()
+                    .find(|locked| locked.identifier == *identifier)
+            })
+            .map_or_else(|| key.clone(), |locked| locked.name.clone());
+
+        match override_ {
+            Some(override_) => println!(
+                "  {}  {}",
+                name.bold(),
+                format!("{key} overridden by {}", override_.display_name()).cyan()
+            ),
+            None => println!("  {}  {}", name.bold(), key.dimmed()),
+        }
+    }
+
+    Ok(())
+}
+
+fn field(label: &str, value: String) {
+    println!("{} {}", format!("{label:12}").dimmed(), value);
+}
@@ -2,9 +2,10 @@ use colored::Colorize;
 use ferinth::{Ferinth, structures::tag::GameVersion};
 use inquire::MultiSelect;
 use libium::{config::structs::ModLoader, iter_ext::IterExt};
+use std::{fs, path::PathBuf};
 use thiserror::Error;
 
-use crate::config::FerriteConfig;
+use crate::config::{FerriteConfig, config_dir};
 use crate::server::ServerInstallation;
 
 #[derive(Debug, Error)]
@@ -23,6 +24,9 @@ pub enum InitError {
 
     #[error("Provide both game versions and mod loaders to create a profile")]
     MissingFields,
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 impl From<inquire::InquireError> for InitError {
@@ -107,40 +111,29 @@ fn sort_mod_loaders(mod_loaders: &mut [ModLoader]) {
     });
 }
 
-/// Creates a new Ferrite configuration
+/// Creates a new Ferrite configuration to be written to `path`
+///
+/// The server jar is installed next to it, where the config's relative paths are resolved
 pub async fn create(
     game_versions: Option<Vec<String>>,
     mod_loaders: Option<Vec<ModLoader>>,
+    path: PathBuf,
 ) -> Result<FerriteConfig> {
-    match (game_versions, mod_loaders) {
-        (Some(game_versions), Some(mut mod_loaders)) => {
-            sort_mod_loaders(&mut mod_loaders);
-            let ServerInstallation {
-                executable,
-                wrapper,
-            } = crate::server::get_server_jar(&game_versions[0], &mod_loaders[0]).await?;
-            Ok(FerriteConfig::new(
-                game_versions,
-                mod_loaders,
-                wrapper,
-                executable,
-            ))
-        }
-        (None, None) => {
-            let game_versions = pick_minecraft_versions().await?;
-            let mut mod_loaders = pick_mod_loader()?;
-            sort_mod_loaders(&mut mod_loaders);
-            let ServerInstallation {
-                executable,
-                wrapper,
-            } = crate::server::get_server_jar(&game_versions[0], &mod_loaders[0]).await?;
-            Ok(FerriteConfig::new(
-                game_versions,
-                mod_loaders,
-                wrapper,
-                executable,
-            ))
-        }
-        _ => Err(InitError::MissingFields),
-    }
+    let (game_versions, mut mod_loaders) = match (game_versions, mod_loaders) {
+        (Some(game_versions), Some(mod_loaders)) => (game_versions, mod_loaders),
+        (None, None) => (pick_minecraft_versions().await?, pick_mod_loader()?),
+        _ => return Err(InitError::MissingFields),
+    };
+    sort_mod_loaders(&mut mod_loaders);
+
+    let dir = config_dir(&path);
+    fs::create_dir_all(dir)?;
+    let ServerInstallation {
+        executable,
+        wrapper,
+    } = crate::server::get_server_jar(&game_versions[0], &mod_loaders[0], dir).await?;
+
+    let mut config = FerriteConfig::new(game_versions, mod_loaders, wrapper, executable);
+    config.path = path;
+    Ok(config)
 }
@@ -0,0 +1,31 @@
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LockError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to parse lockfile: {0}")]
+    Parse(String),
+
+    #[error("Failed to serialize lockfile: {0}")]
+    Serialize(String),
+
+    #[error("No ferrite.lock found. Run `ferrite upgrade` to create one")]
+    Missing,
+
+    #[error("ferrite.lock is out of date with ferrite.yaml. Run `ferrite upgrade` to update it")]
+    Outdated,
+
+    #[error("Invalid URL '{0}' in lockfile")]
+    InvalidUrl(String),
+}
+
+impl From<serde_norway::Error> for LockError {
+    fn from(e: serde_norway::Error) -> Self {
+        LockError::Parse(e.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, LockError>;
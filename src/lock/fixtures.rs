@@ -0,0 +1,48 @@
+//! Lock entries for tests
+
+use super::{LockedDependency, LockedMod, Lockfile, Platform, SkippedMod};
+use libium::config::structs::ModIdentifier;
+
+pub fn modrinth(id: &str) -> ModIdentifier {
+    ModIdentifier::ModrinthProject(id.to_string(), None)
+}
+
+/// A file named after `name` that requires `dependencies`
+///
+/// Names starting with `Dependency: ` are locked as dependencies, like resolving them does
+pub fn locked(name: &str, identifier: ModIdentifier, dependencies: &[ModIdentifier]) -> LockedMod {
+    LockedMod {
+        dependency: name.starts_with("Dependency: "),
+        platform: Platform::from(&identifier),
+        name: name.to_string(),
+        identifier,
+        version_id: String::new(),
+        version: None,
+        filename: format!(
+            "{}.jar",
+            name.trim_start_matches("Dependency: ")
+                .to_lowercase()
+                .replace(' ', "-")
+                .replace('"', "")
+        ),
+        url: String::new(),
+        size: 0,
+        hash: None,
+        dependencies: dependencies
+            .iter()
+            .map(|identifier| LockedDependency {
+                identifier: identifier.clone(),
+                replaces: None,
+            })
+            .collect(),
+    }
+}
+
+pub fn lockfile(mods: Vec<LockedMod>, skipped: Vec<SkippedMod>) -> Lockfile {
+    Lockfile {
+        version: 1,
+        resolved_for: None,
+        mods,
+        skipped,
+    }
+}
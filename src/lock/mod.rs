@@ -0,0 +1,24 @@
+mod error;
+#[cfg(test)]
+pub mod fixtures;
+mod types;
+
+pub use error::{LockError, Result};
+pub use types::{
+    FileHash, HashAlgorithm, LOCKFILE, LockedDependency, LockedMod, Lockfile, Platform,
+    ResolvedFor, SkippedMod, lock_file,
+};
+
+use std::{fs, path::Path};
+
+/// Read the lockfile at `path`, returning `None` if it does not exist
+pub fn load_lock(path: &Path) -> Result<Option<Lockfile>> {
+    if !fs::exists(path)? {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)?;
+    let lockfile = serde_norway::from_str(&content)?;
+
+    Ok(Some(lockfile))
+}
@@ -0,0 +1,211 @@
+use super::error::{LockError, Result};
+use crate::config::FeriumConfig;
+use libium::{
+    config::structs::{ModIdentifier, ModLoader, Profile},
+    upgrade::DownloadData,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::Write, path::Path};
+
+pub const LOCKFILE: &str = "ferrite.lock";
+pub const LOCKFILE_VERSION: i64 = 1;
+
+/// Name of the lockfile of `environment`, every environment resolves its own files
+pub fn lock_file(environment: Option<&str>) -> String {
+    match environment {
+        Some(name) => format!("ferrite.{name}.lock"),
+        None => LOCKFILE.to_string(),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Lockfile {
+    pub version: i64,
+    /// The config the mods were resolved for, missing in lockfiles written by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_for: Option<ResolvedFor>,
+    pub mods: Vec<LockedMod>,
+    /// Mods and dependencies that were resolved but are not installed on the server
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedMod>,
+}
+
+/// The parts of the config other than the mod list that change what resolution picks
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ResolvedFor {
+    pub game_versions: Vec<String>,
+    pub mod_loaders: Vec<ModLoader>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<String, ModIdentifier>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct LockedMod {
+    pub name: String,
+    pub identifier: ModIdentifier,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dependency: bool,
+    pub platform: Platform,
+    pub version_id: String,
+    /// The version number the platform lists the file under, like `mc1.21.1-0.6.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub filename: String,
+    pub url: String,
+    pub size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<FileHash>,
+    /// The projects this file depends on, as they were resolved
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<LockedDependency>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct LockedDependency {
+    /// The project that was resolved for the dependency
+    pub identifier: ModIdentifier,
+    /// The project the file asked for, if an override replaced it with `identifier`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaces: Option<ModIdentifier>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct SkippedMod {
+    pub name: String,
+    pub identifier: ModIdentifier,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dependency: bool,
+    /// Why it is not installed
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Modrinth,
+    CurseForge,
+    GitHub,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha512,
+    Sha1,
+    Md5,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct FileHash {
+    pub algorithm: HashAlgorithm,
+    pub value: String,
+}
+
+impl From<&ModIdentifier> for Platform {
+    fn from(identifier: &ModIdentifier) -> Self {
+        match identifier {
+            ModIdentifier::ModrinthProject(..) => Platform::Modrinth,
+            ModIdentifier::CurseForgeProject(..) => Platform::CurseForge,
+            ModIdentifier::GitHubRepository(..) => Platform::GitHub,
+        }
+    }
+}
+
+impl From<&FeriumConfig> for ResolvedFor {
+    fn from(ferium: &FeriumConfig) -> Self {
+        Self {
+            game_versions: ferium.game_versions.clone(),
+            mod_loaders: ferium.mod_loaders.clone(),
+            overrides: ferium
+                .overrides
+                .iter()
+                .map(|(name, identifier)| (name.clone(), identifier.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Lockfile {
+    pub fn new(
+        resolved_for: ResolvedFor,
+        mut mods: Vec<LockedMod>,
+        mut skipped: Vec<SkippedMod>,
+    ) -> Self {
+        // Keep the order stable so that unchanged resolutions produce identical lockfiles
+        mods.sort_by(|a, b| {
+            a.dependency
+                .cmp(&b.dependency)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+                .then_with(|| a.filename.cmp(&b.filename))
+        });
+        skipped.sort_by(|a, b| {
+            a.dependency
+                .cmp(&b.dependency)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        Self {
+            version: LOCKFILE_VERSION,
+            resolved_for: Some(resolved_for),
+            mods,
+            skipped,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let serialized =
+            serde_norway::to_string(self).map_err(|e| LockError::Serialize(e.to_string()))?;
+
+        let mut file = fs::File::create(path)?;
+        file.write_all(
+            "# This file is generated by ferrite. Do not edit it by hand.\n".as_bytes(),
+        )?;
+        file.write_all(serialized.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Find the entry that was locked for the file at `url`
+    pub fn find_by_url(&self, url: &str) -> Option<&LockedMod> {
+        self.mods.iter().find(|m| m.url == url)
+    }
+
+    /// Whether the mods locked or skipped here are exactly the mods in `profile`, resolved for
+    /// the game versions, mod loaders and overrides in `ferium`
+    pub fn matches(&self, profile: &Profile, ferium: &FeriumConfig) -> bool {
+        if self.resolved_for.as_ref() != Some(&ResolvedFor::from(ferium)) {
+            return false;
+        }
+
+        let locked = self
+            .mods
+            .iter()
+            .filter(|m| !m.dependency)
+            .map(|m| &m.identifier)
+            .chain(
+                self.skipped
+                    .iter()
+                    .filter(|m| !m.dependency)
+                    .map(|m| &m.identifier),
+            )
+            .collect::<Vec<_>>();
+
+        locked.len() == profile.mods.len()
+            && profile
+                .mods
+                .iter()
+                .all(|mod_| locked.contains(&&mod_.identifier))
+    }
+}
+
+impl LockedMod {
+    pub fn to_download_data(&self) -> Result<DownloadData> {
+        Ok(DownloadData {
+            download_url: self
+                .url
+                .parse()
+                .map_err(|_| LockError::InvalidUrl(self.url.clone()))?,
+            output: self.filename.clone().into(),
+            length: self.size,
+            dependencies: Vec::new(),
+            conflicts: Vec::new(),
+        })
+    }
+}
@@ -1,24 +1,38 @@
 mod cli;
 mod config;
+mod diff;
+mod git;
+mod history;
+mod info;
 mod init;
+mod lock;
 mod mods;
 mod scripts;
+mod search;
 mod server;
+mod tree;
 mod update_version;
 mod upgrade;
+mod why;
 
-use std::process::ExitCode;
+use std::{path::PathBuf, process::ExitCode};
 
 use clap::Parser;
-use cli::{Ferrite, SubCommands};
+use cli::{ConfigCommands, Ferrite, SubCommands};
 use colored::Colorize;
-use config::load_config;
-use mods::disable;
+use config::{FerriteConfig, LoadOptions, Severity, load_config};
+use history::{Recorder, load_history};
+use lock::{LockError, load_lock, lock_file};
 use mods::display_successes_failures;
+use mods::{
+    add_tags, disable, enable, mod_matches, pin, pin_mod, pinned_label, tag_mods, unpin,
+    untag_mods, with_tagged,
+};
+use tree::TreeFormat;
 
 use libium::{config::structs::ModIdentifier, iter_ext::IterExt};
 use mods::remove;
-use upgrade::upgrade;
+use upgrade::{install_locked, outdated, prune, prune_orphans, upgrade};
 
 fn main() -> ExitCode {
     #[tokio::main]
@@ -30,7 +44,7 @@ fn main() -> ExitCode {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
             eprintln!("{} {}", "Error:".red(), e);
-            ExitCode::FAILURE
+            e.exit_code()
         }
     }
 }
@@ -49,6 +63,24 @@ pub enum FerriteError {
     #[error("Upgrade error: {0}")]
     Upgrade(#[from] upgrade::UpgradeError),
 
+    #[error("Lockfile error: {0}")]
+    Lock(#[from] LockError),
+
+    #[error("History error: {0}")]
+    History(#[from] history::HistoryError),
+
+    #[error("Git error: {0}")]
+    Git(#[from] git::GitError),
+
+    #[error("Search error: {0}")]
+    Search(#[from] search::SearchError),
+
+    #[error("Info error: {0}")]
+    Info(#[from] info::InfoError),
+
+    #[error("Lookup error: {0}")]
+    Why(#[from] why::WhyError),
+
     #[error("Initialization error: {0}")]
     Init(#[from] init::InitError),
 
@@ -70,67 +102,119 @@ pub enum FerriteError {
     #[error("Libium error: {0}")]
     Libium(#[from] libium::add::Error),
 
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("Invalid identifier format: '{0}'. Expected 'owner/repo' for GitHub")]
     InvalidIdentifierFormat(String),
+
+    #[error("Found {0} problem(s) in the configuration")]
+    InvalidConfig(usize),
+
+    #[error("{0} mod(s) can be updated")]
+    Outdated(usize),
+
+    #[error("{0} mod(s) can be updated, but some mods could not be resolved")]
+    Unresolved(usize),
+}
+
+impl FerriteError {
+    /// 1 when `ferrite outdated` found updates, so monitoring can tell them apart from errors,
+    /// which exit with 2
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::Outdated(_) => ExitCode::from(1),
+            _ => ExitCode::from(2),
+        }
+    }
 }
 
 async fn run() -> Result<(), FerriteError> {
     let cli = Ferrite::parse();
+    if let Some(directory) = &cli.directory {
+        std::env::set_current_dir(directory)?;
+    }
+    let options = LoadOptions {
+        environment: cli.env,
+        path: cli.config,
+    };
 
     match cli.subcommand {
-        SubCommands::Add { identifiers } => {
-            let mut config = load_config()?;
-            let mut profile = config.clone().into();
+        SubCommands::Add { identifiers, tags } => {
+            let config = load_config(&options)?;
+            let (identifiers, pins) = parse_identifiers(identifiers)?;
 
-            let identifiers: Vec<_> = identifiers
-                .into_iter()
-                .map(libium::add::parse_id)
-                .collect::<Result<Vec<_>, _>>()?;
-
-            let (successes, failures) =
-                libium::add(&mut profile, identifiers, true, false, vec![]).await?;
-
-            profile.disabled.retain(|m| {
-                !profile
-                    .mods
-                    .iter()
-                    .any(|mod_| mod_.identifier == m.identifier)
-            });
+            add(config, identifiers, pins, tags).await?;
+        }
 
-            display_successes_failures(
-                &successes.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>(),
-                failures,
+        SubCommands::Install { identifiers, .. } if !identifiers.is_empty() => {
+            println!(
+                "{}",
+                "Warning: `ferrite install <IDENTIFIER>` is deprecated, use `ferrite add` instead"
+                    .yellow()
             );
+            let config = load_config(&options)?;
+            let (identifiers, pins) = parse_identifiers(identifiers)?;
 
-            if config.autoupdate {
-                upgrade(&profile, false, &config.ferium.overrides).await?;
-            }
+            add(config, identifiers, pins, Vec::new()).await?;
+        }
 
-            config.update(profile);
+        SubCommands::Search {
+            query,
+            platform,
+            json,
+        } => {
+            let config = load_config(&options)?;
+            let query = query.join(" ");
+            let results = search::search(&query, &config, platform).await?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            } else if results.is_empty() {
+                println!("{}", format!("No mods found for '{query}'").yellow());
+            } else {
+                let identifiers = search::select(&results)?;
+                if !identifiers.is_empty() {
+                    add(config, identifiers, Vec::new(), Vec::new()).await?;
+                }
+            }
         }
 
-        SubCommands::List => {
-            let config = load_config()?;
+        SubCommands::List { tag } => {
+            let config = load_config(&options)?;
+            let lockfile = load_lock(&config.lock_path())?;
+
+            // A tag lists its disabled mods too, to see which of its mods are switched on
+            let mods = config
+                .ferium
+                .mods
+                .iter()
+                .map(|mod_| (mod_, false))
+                .chain(config.ferium.disabled.iter().map(|mod_| (mod_, true)))
+                .filter(|(mod_, disabled)| match &tag {
+                    Some(tag) => mod_.has_tag(tag),
+                    None => !disabled,
+                })
+                .collect::<Vec<_>>();
 
             println!(
-                "{} mods on {} {}\n",
-                config.ferium.mods.len().to_string().yellow(),
+                "{} mods{} on {} {}\n",
+                mods.len().to_string().yellow(),
+                tag.as_ref()
+                    .map(|tag| format!(" tagged {}", tag.blue()))
+                    .unwrap_or_default(),
                 config.ferium.mod_loaders.iter().display(", ").purple(),
                 config.ferium.game_versions.iter().display(", ").green(),
             );
-            for mod_ in &config.ferium.mods {
+            for (mod_, disabled) in mods {
                 println!(
-                    "{:20}  {}",
+                    "{:20}  {}{}{}{}  {}",
                     match &mod_.identifier {
                         ModIdentifier::CurseForgeProject(id, _) => {
                             format!("{} {:8}", "CF".red(), id.to_string().dimmed())
                         }
-                        ModIdentifier::ModrinthProject(id, version) => {
-                            if version.is_some() {
-                                format!("{} {:8}", "CF".red(), id.dimmed())
-                            } else {
-                                format!("{} {:8}", "MR".green(), id.dimmed())
-                            }
+                        ModIdentifier::ModrinthProject(id, _) => {
+                            format!("{} {:8}", "MR".green(), id.dimmed())
                         }
                         ModIdentifier::GitHubRepository(..) => "GH".purple().to_string(),
                     },
@@ -141,48 +225,236 @@ async fn run() -> Result<(), FerriteError> {
                             format!("{}/{}", owner.dimmed(), repo.bold())
                         }
                     },
+                    pinned_label(&mod_.identifier, lockfile.as_ref())
+                        .map(|version| format!(" {}", format!("pinned to {version}").cyan()))
+                        .unwrap_or_default(),
+                    if disabled {
+                        format!(" {}", "(disabled)".yellow())
+                    } else {
+                        String::new()
+                    },
+                    mod_.tags
+                        .iter()
+                        .map(|tag| format!(" {}", format!("#{tag}").blue()))
+                        .collect::<String>(),
+                    config
+                        .origin(mod_)
+                        .map(|origin| format!("(from {})", origin.display()).dimmed().to_string())
+                        .unwrap_or_default(),
                 );
             }
+
+            if tag.is_none()
+                && let Some(lockfile) = &lockfile
+                && !lockfile.skipped.is_empty()
+            {
+                println!("\n{}", "Skipped on the server".bold());
+                for skipped in &lockfile.skipped {
+                    println!("  {}  {}", skipped.name.bold(), skipped.reason.dimmed());
+                }
+            }
         }
 
-        SubCommands::Remove { mod_names } => {
-            let mut config = load_config()?;
+        SubCommands::Info { mod_name } => {
+            let config = load_config(&options)?;
+            let (mod_, disabled) = config
+                .ferium
+                .mods
+                .iter()
+                .map(|mod_| (mod_, false))
+                .chain(config.ferium.disabled.iter().map(|mod_| (mod_, true)))
+                .find(|(mod_, _)| mod_matches(mod_, &mod_name))
+                .ok_or(mods::ModError::NotFound(mod_name))?;
+
+            let lockfile = load_lock(&config.lock_path())?;
+            info::show(&config, mod_, disabled, lockfile.as_ref()).await?;
+        }
+
+        SubCommands::Remove { mod_names, tag } => {
+            let mut config = load_config(&options)?;
+            let mod_names = with_tagged(&config, mod_names, tag.as_deref(), false)?;
+            let recorder = Recorder::start(&config)?;
             let mut profile = config.clone().into();
 
             remove(&mut profile, mod_names)?;
+            let changed = git::removed_mods(&config.ferium.mods, &profile.mods);
 
-            if config.autoupdate {
-                upgrade(&profile, false, &config.ferium.overrides).await?;
-            }
+            config.update(&profile)?;
 
-            config.update(profile);
+            // Recorded once the files changed, so undo can move them back, even if that failed
+            let installed = if config.autoupdate {
+                upgrade(&profile, false, &config).await
+            } else {
+                prune_orphans(&profile, &config.lock_path()).map(drop)
+            };
+            recorder.finish()?;
+            installed?;
+            if !changed.is_empty() {
+                git::commit(&config, "remove", &format!("remove {changed}"))?;
+            }
         }
 
-        SubCommands::Disable { mod_names } => {
-            let mut config = load_config()?;
+        SubCommands::Disable { mod_names, tag } => {
+            let mut config = load_config(&options)?;
+            let mod_names = with_tagged(&config, mod_names, tag.as_deref(), false)?;
+            let recorder = Recorder::start(&config)?;
             let mut profile = config.clone().into();
 
             disable(&mut profile, mod_names)?;
+            let changed = git::removed_mods(&config.ferium.mods, &profile.mods);
+
+            config.update(&profile)?;
 
-            if config.autoupdate {
-                upgrade(&profile, false, &config.ferium.overrides).await?;
+            // Recorded once the files changed, so undo can move them back, even if that failed
+            let installed = if config.autoupdate {
+                upgrade(&profile, false, &config).await
+            } else {
+                prune_orphans(&profile, &config.lock_path()).map(drop)
+            };
+            recorder.finish()?;
+            installed?;
+            if !changed.is_empty() {
+                git::commit(&config, "disable", &format!("disable {changed}"))?;
             }
+        }
 
-            config.update(profile);
+        SubCommands::Enable { mod_names, tag } => {
+            let mut config = load_config(&options)?;
+            let mod_names = with_tagged(&config, mod_names, tag.as_deref(), true)?;
+            let recorder = Recorder::start(&config)?;
+            let mut profile = config.clone().into();
+
+            let lockfile = load_lock(&config.lock_path())?;
+            enable(&mut profile, mod_names, lockfile.as_ref())?;
+            let changed = git::removed_mods(&profile.mods, &config.ferium.mods);
+
+            config.update(&profile)?;
+
+            let installed = if config.autoupdate {
+                upgrade(&profile, false, &config).await
+            } else {
+                Ok(())
+            };
+            recorder.finish()?;
+            installed?;
+            if !changed.is_empty() {
+                git::commit(&config, "enable", &format!("enable {changed}"))?;
+            }
         }
 
         SubCommands::Upgrade => {
-            let config = load_config()?;
+            let config = load_config(&options)?;
             let profile = config.clone().into();
 
-            upgrade(&profile, true, &config.ferium.overrides).await?;
+            upgrade(&profile, true, &config).await?;
+            git::commit(&config, "upgrade", "upgrade mods")?;
+        }
+
+        SubCommands::Outdated => {
+            let config = load_config(&options)?;
+
+            let summary = outdated(&config.clone().into(), &config).await?;
+            if summary.unresolved {
+                return Err(FerriteError::Unresolved(summary.updates));
+            }
+            if summary.updates > 0 {
+                return Err(FerriteError::Outdated(summary.updates));
+            }
+        }
+
+        SubCommands::Prune => {
+            let config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
+
+            prune(&config.clone().into(), &config.lock_path())?;
+            recorder.finish()?;
+            git::commit(&config, "prune", "prune orphaned dependencies")?;
+        }
+
+        SubCommands::Install { locked, .. } => {
+            let config = load_config(&options)?;
+            let profile = config.clone().into();
+
+            match load_lock(&config.lock_path())? {
+                Some(lockfile) if lockfile.matches(&profile, &config.ferium) => {
+                    install_locked(&profile, &lockfile).await?;
+                }
+                Some(_) if locked => return Err(LockError::Outdated.into()),
+                None if locked => return Err(LockError::Missing.into()),
+                _ => upgrade(&profile, true, &config).await?,
+            }
+        }
+
+        SubCommands::Pin { mod_name, version } => {
+            let mut config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
+
+            let version = pin(&mut config, &mod_name, version.as_deref()).await?;
+
+            config.write_config()?;
+            let installed = if config.autoupdate {
+                upgrade(&config.clone().into(), false, &config).await
+            } else {
+                Ok(())
+            };
+            recorder.finish()?;
+            installed?;
+            git::commit(&config, "pin", &format!("pin {mod_name} {version}"))?;
+        }
+
+        SubCommands::Unpin { mod_names } => {
+            let mut config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
+
+            unpin(&mut config, mod_names.clone())?;
+
+            config.write_config()?;
+            let installed = if config.autoupdate {
+                upgrade(&config.clone().into(), false, &config).await
+            } else {
+                Ok(())
+            };
+            recorder.finish()?;
+            installed?;
+            git::commit(&config, "unpin", &format!("unpin {}", mod_names.join(", ")))?;
+        }
+
+        SubCommands::Tag { tag, mod_names } => {
+            let mut config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
+
+            let tagged = tag_mods(&mut config, &tag, mod_names)?;
+
+            config.write_config()?;
+            recorder.finish()?;
+            if !tagged.is_empty() {
+                git::commit(&config, "tag", &format!("tag {} {tag}", tagged.join(", ")))?;
+            }
+        }
+
+        SubCommands::Untag { tag, mod_names } => {
+            let mut config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
+
+            let untagged = untag_mods(&mut config, &tag, mod_names)?;
+
+            config.write_config()?;
+            recorder.finish()?;
+            if !untagged.is_empty() {
+                git::commit(
+                    &config,
+                    "untag",
+                    &format!("untag {} {tag}", untagged.join(", ")),
+                )?;
+            }
         }
 
         SubCommands::Override {
             mod_name,
             identifier,
         } => {
-            let mut config = load_config()?;
+            let mut config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
 
             let parsed_identifier: ModIdentifier = if identifier.contains('/') {
                 let split = identifier
@@ -201,18 +473,27 @@ async fn run() -> Result<(), FerriteError> {
                 .insert(mod_name.clone(), parsed_identifier);
 
             config.write_config()?;
+            recorder.finish()?;
+            git::commit(
+                &config,
+                "override",
+                &format!("override {mod_name} -> {identifier}"),
+            )?;
         }
 
         SubCommands::Init {
             game_versions,
             mod_loaders,
         } => {
-            let config = init::create(game_versions, mod_loaders).await?;
+            let path = options
+                .path
+                .unwrap_or_else(|| PathBuf::from(config::CONFIG_FILE));
+            let mut config = init::create(game_versions, mod_loaders, path).await?;
             config.write_config()?;
         }
 
         SubCommands::Start => {
-            let config = load_config()?;
+            let config = load_config(&options)?;
 
             let wrapper = config
                 .server
@@ -221,6 +502,7 @@ async fn run() -> Result<(), FerriteError> {
             let parts = wrapper.split(' ').collect_vec();
 
             std::process::Command::new(parts[0])
+                .current_dir(config.dir())
                 .args(&parts[1..])
                 .stdin(std::process::Stdio::inherit())
                 .stdout(std::process::Stdio::inherit())
@@ -230,19 +512,359 @@ async fn run() -> Result<(), FerriteError> {
         }
 
         SubCommands::Script { script } => {
-            let mut config = load_config()?;
+            let mut config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
 
             scripts::run(&mut config, &script)?;
 
             config.write_config()?;
+            recorder.finish()?;
+            git::commit(&config, "script", &format!("script {script}"))?;
+        }
+
+        SubCommands::Config { subcommand } => match subcommand {
+            ConfigCommands::Schema => println!("{}", config::schema()?),
+
+            ConfigCommands::Validate => {
+                let config = load_config(&options)?;
+                let issues = config::validate(&config, load_lock(&config.lock_path())?.as_ref());
+
+                for issue in &issues {
+                    match issue.severity {
+                        Severity::Error => println!("{} {}", "×".red(), issue.message),
+                        Severity::Warning => println!("{} {}", "⚠".yellow(), issue.message),
+                    }
+                }
+
+                let errors = issues
+                    .iter()
+                    .filter(|issue| issue.severity == Severity::Error)
+                    .count();
+                if errors > 0 {
+                    return Err(FerriteError::InvalidConfig(errors));
+                }
+                println!("{} {} is valid", "✓".green(), config.path.display());
+            }
+
+            ConfigCommands::Migrate { dry_run: true } => match config::migration_diff(&options)? {
+                Some(diff) => print_diff(&diff),
+                None => println!("{}", "ferrite.yaml is already up to date".dimmed()),
+            },
+
+            ConfigCommands::Get { key } => {
+                let document = config::read_config(&options)?;
+                println!("{}", config::display(&config::get(&document, &key)?)?);
+            }
+
+            ConfigCommands::Set { key, value } => {
+                let mut config = load_config(&options)?;
+                let recorder = Recorder::start(&config)?;
+                config::set(&mut config, &key, &value)?;
+                config.write_config()?;
+                recorder.finish()?;
+                // The value may be a secret, and commit messages are often pushed
+                git::commit(&config, "config", &format!("config set {key}"))?;
+            }
+
+            ConfigCommands::Unset { key } => {
+                let mut config = load_config(&options)?;
+                let recorder = Recorder::start(&config)?;
+                config::unset(&mut config, &key)?;
+                config.write_config()?;
+                recorder.finish()?;
+                git::commit(&config, "config", &format!("config unset {key}"))?;
+            }
+
+            ConfigCommands::Convert { to } => {
+                let converted = config::convert_config(&options, to)?;
+                println!(
+                    "{} Converted the config to {}, the original was kept as a backup",
+                    "✓".green(),
+                    converted.display()
+                );
+            }
+
+            ConfigCommands::Migrate { dry_run: false } => {
+                if !config::migrate_config(&options)? {
+                    println!("{}", "ferrite.yaml is already up to date".dimmed());
+                }
+            }
+        },
+
+        SubCommands::Diff {
+            other: Some(other),
+            json,
+            ..
+        } => {
+            let config = load_config(&options)?;
+            // Only read, so comparing never migrates it or asks for its secrets
+            let other = config::peek_config(&LoadOptions {
+                environment: None,
+                path: Some(other),
+            })?;
+
+            let diff = diff::compare(&config, &other);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                diff::print_config_diff(&diff);
+            }
+        }
+
+        SubCommands::Diff { json, .. } => {
+            let config = load_config(&options)?;
+            let lockfile = load_lock(&config.lock_path())?.ok_or(LockError::Missing)?;
+
+            let diff = diff::installed(&config, &lockfile, &config.output_dir())?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                diff::print_installed_diff(&diff, &config.output_path);
+            }
+        }
+
+        SubCommands::Tree { format } => {
+            let path = config::find_config(&options)?;
+            let lock_path =
+                config::config_dir(&path).join(lock_file(options.environment.as_deref()));
+            let lockfile = load_lock(&lock_path)?.ok_or(LockError::Missing)?;
+
+            let graph = tree::Graph::new(&lockfile);
+            match format {
+                TreeFormat::Text => print!("{}", tree::text(&graph)),
+                TreeFormat::Dot => print!("{}", tree::dot(&graph)),
+                TreeFormat::Json => println!("{}", serde_json::to_string_pretty(&graph)?),
+            }
+        }
+
+        SubCommands::Why { target } => {
+            let config = load_config(&options)?;
+            let lockfile = load_lock(&config.lock_path())?.ok_or(LockError::Missing)?;
+
+            why::why(&config, &lockfile, &target)?;
+        }
+
+        SubCommands::History { diff } => {
+            let path = config::find_config(&options)?;
+            let history = load_history(config::config_dir(&path))?;
+            if history.is_empty() {
+                println!("{}", "No changes have been recorded yet".dimmed());
+            }
+
+            let undone: Vec<usize> = history
+                .iter()
+                .flat_map(|entry| entry.undoes.iter().copied())
+                .collect();
+            for entry in &history {
+                let (added, removed) = entry.line_counts();
+                let summary = format!(
+                    "{} {}{}",
+                    format!("+{added}").green(),
+                    format!("-{removed}").red(),
+                    match entry.files.len() {
+                        0 => String::new(),
+                        1 => ", 1 file".to_string(),
+                        files => format!(", {files} files"),
+                    },
+                );
+                let command = if undone.contains(&entry.id) {
+                    format!("{} (undone)", entry.command).dimmed().to_string()
+                } else {
+                    entry.command.bold().to_string()
+                };
+                println!(
+                    "{:>4}  {}  {}  {}",
+                    format!("#{}", entry.id).yellow(),
+                    entry
+                        .timestamp
+                        .to_zoned(jiff::tz::TimeZone::system())
+                        .strftime("%Y-%m-%d %H:%M")
+                        .to_string()
+                        .dimmed(),
+                    command,
+                    summary,
+                );
+
+                if diff {
+                    print_diff(&entry.diff);
+                    println!();
+                }
+            }
+        }
+
+        SubCommands::Undo { count } => {
+            let path = config::find_config(&options)?;
+            let undone = history::undo(config::config_dir(&path), count)?;
+
+            for entry in &undone.entries {
+                println!(
+                    "{} Undid #{}  {}",
+                    "✓".green(),
+                    entry.id,
+                    entry.command.bold()
+                );
+            }
+            if !undone.missing.is_empty() {
+                println!(
+                    "{}",
+                    format!(
+                        "Warning: {} are no longer in .old and will be downloaded by the next `ferrite upgrade`",
+                        undone.missing.iter().display(", ")
+                    )
+                    .yellow()
+                );
+            }
+
+            // The restored config decides whether the undo is committed
+            if let Some(entry) = undone.entries.first() {
+                let config = load_config(&LoadOptions {
+                    path: Some(entry.config.clone()),
+                    ..options
+                })?;
+                let message = undone
+                    .entries
+                    .iter()
+                    .map(|entry| git::redact(entry.command.trim_start_matches("ferrite ")))
+                    .display("; ");
+                git::commit(&config, "undo", &format!("undo {message}"))?;
+            }
         }
 
         SubCommands::UpdateVersion { version } => {
-            let mut config = load_config()?;
+            let mut config = load_config(&options)?;
+            let recorder = Recorder::start(&config)?;
+            let from = config
+                .ferium
+                .game_versions
+                .first()
+                .cloned()
+                .unwrap_or_default();
+
+            let updated = update_version::upgrade_version(&mut config, version).await;
+            recorder.finish()?;
+            updated?;
+            if let Some(to) = config.ferium.game_versions.first() {
+                git::commit(
+                    &config,
+                    "update-version",
+                    &format!("update-version {from} -> {to}"),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the identifiers given to `ferrite add`
+///
+/// `slug@version` adds the mod pinned to that version, these are returned as
+/// `(identifier, version)` next to the parsed identifiers
+fn parse_identifiers(
+    identifiers: Vec<String>,
+) -> Result<(Vec<ModIdentifier>, Vec<(String, String)>), FerriteError> {
+    let mut pins = Vec::new();
+    let identifiers = identifiers
+        .into_iter()
+        .map(|identifier| match identifier.split_once('@') {
+            Some((id, version)) => {
+                pins.push((id.to_string(), version.to_string()));
+                libium::add::parse_id(id.to_string())
+            }
+            None => libium::add::parse_id(identifier),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((identifiers, pins))
+}
 
-            update_version::upgrade_version(&mut config, version).await?;
+/// Add `identifiers` to the config through libium, like `ferrite add` does
+///
+/// `pins` are the mods to pin right after adding them, as `(identifier, version)`, and `tags`
+/// are given to every mod that was added
+async fn add(
+    mut config: FerriteConfig,
+    identifiers: Vec<ModIdentifier>,
+    pins: Vec<(String, String)>,
+    tags: Vec<String>,
+) -> Result<(), FerriteError> {
+    let recorder = Recorder::start(&config)?;
+    let before = config
+        .ferium
+        .mods
+        .iter()
+        .map(|mod_| mod_.identifier.display_name())
+        .collect::<Vec<_>>();
+    let mut profile = config.clone().into();
+
+    let (successes, failures) = libium::add(&mut profile, identifiers, true, false, vec![]).await?;
+
+    profile.disabled.retain(|m| {
+        !profile
+            .mods
+            .iter()
+            .any(|mod_| mod_.identifier == m.identifier)
+    });
+
+    let successes = successes.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>();
+    display_successes_failures(&successes, failures);
+
+    for (identifier, version) in &pins {
+        match profile
+            .mods
+            .iter_mut()
+            .find(|mod_| mod_matches(mod_, identifier))
+        {
+            Some(mod_) => {
+                pin_mod(mod_, Some(version.as_str()), &config).await?;
+            }
+            None => println!(
+                "{}",
+                format!("Could not pin {identifier}, run `ferrite pin <name> {version}`").yellow()
+            ),
         }
     }
 
+    config.update(&profile)?;
+    if !tags.is_empty() {
+        for mod_ in config
+            .ferium
+            .mods
+            .iter_mut()
+            .filter(|mod_| !before.contains(&mod_.identifier.display_name()))
+        {
+            add_tags(mod_, &tags);
+        }
+        config.write_config()?;
+    }
+
+    let installed = if config.autoupdate {
+        upgrade(&profile, false, &config).await
+    } else {
+        Ok(())
+    };
+    recorder.finish()?;
+    installed?;
+    if !successes.is_empty() {
+        git::commit(&config, "add", &format!("add {}", successes.join(", ")))?;
+    }
+
     Ok(())
 }
+
+/// Print a unified diff with additions and removals colored
+fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", line.bold());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else {
+            println!("{line}");
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use super::error::Result;
+use super::select::select_mods;
+use crate::lock::Lockfile;
+use colored::Colorize as _;
+use libium::{
+    config::structs::{Mod, Profile},
+    iter_ext::IterExt as _,
+};
+use std::fs;
+
+/// If `to_enable` is empty, display a list of disabled projects in the profile to select from and enable selected ones
+///
+/// Else, search the given strings with the disabled projects' name and IDs and enable them
+pub fn enable(
+    profile: &mut Profile,
+    to_enable: Vec<String>,
+    lockfile: Option<&Lockfile>,
+) -> Result<()> {
+    let indices = select_mods(&profile.disabled, to_enable, "Select mods to enable")?;
+
+    let mut enabled = Vec::new();
+    for index in indices {
+        let mod_ = profile.disabled.swap_remove(index);
+        enabled.push(mod_.name.clone());
+        restore_jars(profile, &mod_, lockfile)?;
+        profile.mods.push(mod_);
+    }
+
+    if !enabled.is_empty() {
+        println!(
+            "Enabled {}",
+            enabled.iter().map(|txt| txt.bold()).display(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Rename the `.disabled` jars of `mod_` in the output directory back to their original name
+///
+/// The jars are found by the filenames locked for the mod, without a lockfile there is nothing
+/// to restore and the next upgrade downloads them again
+fn restore_jars(profile: &Profile, mod_: &Mod, lockfile: Option<&Lockfile>) -> Result<()> {
+    let filenames = lockfile
+        .into_iter()
+        .flat_map(|lockfile| &lockfile.mods)
+        .filter(|locked| locked.identifier == mod_.identifier)
+        .map(|locked| &locked.filename);
+
+    for filename in filenames {
+        let disabled = profile.output_dir.join(format!("{filename}.disabled"));
+        if disabled.is_file() {
+            fs::rename(&disabled, profile.output_dir.join(filename))?;
+            println!("{} Restored {}", "✓".green(), filename.dimmed());
+        }
+    }
+
+    Ok(())
+}
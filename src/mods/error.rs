@@ -10,6 +10,27 @@ pub enum ModError {
 
     #[error("Inquire error: {0}")]
     Inquire(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Modrinth API error: {0}")]
+    ModrinthApi(#[from] ferinth::Error),
+
+    #[error("CurseForge API error: {0}")]
+    CurseApi(#[from] furse::Error),
+
+    #[error("{1} has no version '{0}'")]
+    VersionNotFound(String, String),
+
+    #[error("{0} has no versions for the configured game versions and mod loaders")]
+    NoVersions(String),
+
+    #[error("{0} is a GitHub repository, only Modrinth and CurseForge mods can be pinned")]
+    PinUnsupported(String),
+
+    #[error("No mods in `ferium.{1}` are tagged '{0}'")]
+    NotTagged(String, String),
 }
 
 impl From<inquire::InquireError> for ModError {
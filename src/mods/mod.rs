@@ -1,9 +1,17 @@
 mod add;
 mod disable;
+mod enable;
 mod error;
+mod pin;
 mod remove;
+mod select;
+mod tag;
 
 pub use add::display_successes_failures;
 pub use disable::disable;
+pub use enable::enable;
 pub use error::ModError;
+pub use pin::{pin, pin_mod, pinned_label, pinned_version, unpin};
 pub use remove::remove;
+pub use select::mod_matches;
+pub use tag::{add_tags, tag_mods, untag_mods, with_tagged};
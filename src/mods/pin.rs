@@ -0,0 +1,228 @@
+use super::error::{ModError, Result};
+use super::select::mod_matches;
+use crate::config::FerriteConfig;
+use crate::lock::Lockfile;
+use crate::search::modrinth_loader;
+use colored::Colorize as _;
+use inquire::Select;
+use libium::{
+    CURSEFORGE_API, MODRINTH_API,
+    config::structs::{Mod, ModIdentifier},
+    iter_ext::IterExt as _,
+};
+
+/// A version of a project that a mod can be pinned to
+struct Version {
+    identifier: ModIdentifier,
+    number: String,
+    channel: String,
+    date: String,
+    compatible: bool,
+}
+
+/// The version `identifier` is pinned to, `None` if it follows the latest compatible version
+pub fn pinned_version(identifier: &ModIdentifier) -> Option<String> {
+    match identifier {
+        ModIdentifier::ModrinthProject(_, version) => version.as_ref().map(ToString::to_string),
+        ModIdentifier::CurseForgeProject(_, version) => version.as_ref().map(ToString::to_string),
+        ModIdentifier::GitHubRepository(_, version) => version.as_ref().map(ToString::to_string),
+    }
+}
+
+/// The version number `identifier` is pinned to, as it was locked in `lockfile`
+///
+/// Falls back to the version ID if the pinned version has not been locked yet
+pub fn pinned_label(identifier: &ModIdentifier, lockfile: Option<&Lockfile>) -> Option<String> {
+    let id = pinned_version(identifier)?;
+    let number = lockfile
+        .into_iter()
+        .flat_map(|lockfile| &lockfile.mods)
+        .find(|locked| !locked.dependency && locked.identifier == *identifier)
+        .and_then(|locked| locked.version.clone());
+
+    Some(number.unwrap_or(id))
+}
+
+/// Pin the mod named `name` to `version`, or to a version picked from the compatible ones
+///
+/// Returns the version number it was pinned to
+pub async fn pin(config: &mut FerriteConfig, name: &str, version: Option<&str>) -> Result<String> {
+    let index = config
+        .ferium
+        .mods
+        .iter()
+        .position(|mod_| mod_matches(mod_, name))
+        .ok_or_else(|| ModError::NotFound(name.to_string()))?;
+
+    let mut mod_ = config.ferium.mods[index].clone();
+    let number = pin_mod(&mut mod_, version, config).await?;
+    config.ferium.mods[index] = mod_;
+
+    Ok(number)
+}
+
+/// Pin `mod_` to `version`, which may be a version number or ID, or to a version picked from
+/// the ones compatible with `config`'s game versions and mod loaders
+pub async fn pin_mod(
+    mod_: &mut Mod,
+    version: Option<&str>,
+    config: &FerriteConfig,
+) -> Result<String> {
+    let versions = list_versions(&mod_.identifier, config).await?;
+
+    let pinned = match version {
+        Some(version) => versions
+            .into_iter()
+            .find(|candidate| {
+                candidate.number == version
+                    || pinned_version(&candidate.identifier).as_deref() == Some(version)
+            })
+            .ok_or_else(|| ModError::VersionNotFound(version.to_string(), mod_.name.clone()))?,
+        None => {
+            let current = pinned_version(&mod_.identifier);
+            let mut compatible = versions
+                .into_iter()
+                .filter(|candidate| candidate.compatible)
+                .collect_vec();
+            if compatible.is_empty() {
+                return Err(ModError::NoVersions(mod_.name.clone()));
+            }
+
+            let options = compatible
+                .iter()
+                .map(|candidate| {
+                    let label = format!(
+                        "{:30}  {:7}  {}",
+                        candidate.number, candidate.channel, candidate.date
+                    );
+                    if pinned_version(&candidate.identifier) == current {
+                        format!("{label}  {}", "(pinned)".cyan())
+                    } else {
+                        label
+                    }
+                })
+                .collect_vec();
+            let selected = Select::new(&format!("Pin {} to", mod_.name), options).raw_prompt()?;
+            compatible.swap_remove(selected.index)
+        }
+    };
+
+    if !pinned.compatible {
+        println!(
+            "{}",
+            format!(
+                "Warning: {} is not listed for {} on {}",
+                pinned.number,
+                config.ferium.mod_loaders.iter().display(", "),
+                config.ferium.game_versions.iter().display(", ")
+            )
+            .yellow()
+        );
+    }
+    println!(
+        "{} Pinned {} to {}",
+        "✓".green(),
+        mod_.name.bold(),
+        pinned.number.cyan()
+    );
+    mod_.identifier = pinned.identifier;
+
+    Ok(pinned.number)
+}
+
+/// Make the mods named in `names` follow the latest compatible version again
+pub fn unpin(config: &mut FerriteConfig, names: Vec<String>) -> Result<()> {
+    let mut unpinned = Vec::new();
+    for name in names {
+        let mod_ = config
+            .ferium
+            .mods
+            .iter_mut()
+            .find(|mod_| mod_matches(mod_, &name))
+            .ok_or(ModError::NotFound(name))?;
+
+        if pinned_version(&mod_.identifier).is_none() {
+            println!("{}", format!("{} is not pinned", mod_.name).yellow());
+            continue;
+        }
+        mod_.identifier = match &mod_.identifier {
+            ModIdentifier::ModrinthProject(id, _) => {
+                ModIdentifier::ModrinthProject(id.clone(), None)
+            }
+            ModIdentifier::CurseForgeProject(id, _) => ModIdentifier::CurseForgeProject(*id, None),
+            ModIdentifier::GitHubRepository(repo, _) => {
+                ModIdentifier::GitHubRepository(repo.clone(), None)
+            }
+        };
+        unpinned.push(mod_.name.clone());
+    }
+
+    if !unpinned.is_empty() {
+        println!(
+            "Unpinned {}",
+            unpinned.iter().map(|txt| txt.bold()).display(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Every version of the project `identifier` refers to, newest first
+async fn list_versions(identifier: &ModIdentifier, config: &FerriteConfig) -> Result<Vec<Version>> {
+    let game_versions = &config.ferium.game_versions;
+    let loaders = config
+        .ferium
+        .mod_loaders
+        .iter()
+        .map(modrinth_loader)
+        .collect_vec();
+
+    let mut versions = match identifier {
+        ModIdentifier::ModrinthProject(id, _) => MODRINTH_API
+            .version_list(id)
+            .await?
+            .into_iter()
+            .map(|version| Version {
+                compatible: version
+                    .game_versions
+                    .iter()
+                    .any(|game_version| game_versions.contains(game_version))
+                    && version
+                        .loaders
+                        .iter()
+                        .any(|loader| loaders.contains(&loader.as_str())),
+                identifier: ModIdentifier::ModrinthProject(id.clone(), Some(version.id)),
+                number: version.version_number,
+                channel: format!("{:?}", version.version_type).to_lowercase(),
+                date: version.date_published.date_naive().to_string(),
+            })
+            .collect_vec(),
+        // CurseForge lists the mod loaders among the game versions
+        ModIdentifier::CurseForgeProject(id, _) => CURSEFORGE_API
+            .get_mod_files(*id)
+            .await?
+            .into_iter()
+            .map(|file| Version {
+                compatible: file
+                    .game_versions
+                    .iter()
+                    .any(|game_version| game_versions.contains(game_version))
+                    && file.game_versions.iter().any(|game_version| {
+                        loaders
+                            .iter()
+                            .any(|loader| game_version.eq_ignore_ascii_case(loader))
+                    }),
+                identifier: ModIdentifier::CurseForgeProject(*id, Some(file.id)),
+                number: file.display_name,
+                channel: format!("{:?}", file.release_type).to_lowercase(),
+                date: file.file_date.date_naive().to_string(),
+            })
+            .collect_vec(),
+        ModIdentifier::GitHubRepository(..) => {
+            return Err(ModError::PinUnsupported(identifier.display_name()));
+        }
+    };
+
+    versions.sort_by(|a, b| b.date.cmp(&a.date));
+    Ok(versions)
+}
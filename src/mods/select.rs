@@ -0,0 +1,67 @@
+use super::error::{ModError, Result};
+use inquire::MultiSelect;
+use libium::{
+    config::structs::{Mod, ModIdentifier},
+    iter_ext::IterExt as _,
+};
+
+/// If `names` is empty, display a list of the projects in `mods` to select from with `prompt`
+///
+/// Else, search the given strings with the projects' name and IDs. The indices of the
+/// projects are returned without duplicates and in descending order, so that removing them
+/// one after another with `swap_remove` does not move the ones that are still to be removed.
+pub fn select_mods(mods: &[Mod], names: Vec<String>, prompt: &str) -> Result<Vec<usize>> {
+    let mut indices = if names.is_empty() {
+        let mod_info = mods
+            .iter()
+            .map(|mod_| {
+                format!(
+                    "{:11}  {}",
+                    match &mod_.identifier {
+                        ModIdentifier::CurseForgeProject(id, _) =>
+                            format!("CF {:8}", id.to_string()),
+                        ModIdentifier::ModrinthProject(id, _) => format!("MR {id:8}"),
+                        ModIdentifier::GitHubRepository(..) => "GH".to_string(),
+                    },
+                    match &mod_.identifier {
+                        ModIdentifier::ModrinthProject(..)
+                        | ModIdentifier::CurseForgeProject(..) => mod_.name.clone(),
+                        ModIdentifier::GitHubRepository((owner, repo), _) =>
+                            format!("{}/{}", owner, repo),
+                    },
+                )
+            })
+            .collect_vec();
+        MultiSelect::new(prompt, mod_info)
+            .raw_prompt_skippable()?
+            .unwrap_or_default()
+            .iter()
+            .map(|o| o.index)
+            .collect_vec()
+    } else {
+        let mut indices = Vec::new();
+        for name in names {
+            match mods.iter().position(|mod_| mod_matches(mod_, &name)) {
+                Some(index) => indices.push(index),
+                None => return Err(ModError::NotFound(name)),
+            }
+        }
+        indices
+    };
+
+    // A mod can be given more than once, by its name and by its ID for example
+    indices.sort_unstable();
+    indices.dedup();
+    indices.reverse();
+    Ok(indices)
+}
+
+/// Whether `name` is the name, ID or slug of `mod_`, ignoring case
+pub fn mod_matches(mod_: &Mod, name: &str) -> bool {
+    mod_.name.eq_ignore_ascii_case(name)
+        || mod_.identifier.display_name().eq_ignore_ascii_case(name)
+        || mod_
+            .slug
+            .as_ref()
+            .is_some_and(|slug| name.eq_ignore_ascii_case(slug))
+}
@@ -0,0 +1,143 @@
+use super::error::{ModError, Result};
+use super::select::mod_matches;
+use crate::config::{FerriteConfig, ModConfig};
+use colored::Colorize as _;
+use libium::iter_ext::IterExt as _;
+
+/// `names` and the names of the mods tagged `tag`, each only once
+///
+/// The mods are looked up in `ferium.disabled` instead of `ferium.mods` if `disabled`
+pub fn with_tagged(
+    config: &FerriteConfig,
+    mut names: Vec<String>,
+    tag: Option<&str>,
+    disabled: bool,
+) -> Result<Vec<String>> {
+    if let Some(tag) = tag {
+        for name in tagged(config, tag, disabled)? {
+            if !names.iter().any(|other| other.eq_ignore_ascii_case(&name)) {
+                names.push(name);
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+/// Names of the mods tagged `tag`, in `ferium.disabled` instead of `ferium.mods` if `disabled`
+fn tagged(config: &FerriteConfig, tag: &str, disabled: bool) -> Result<Vec<String>> {
+    let (mods, list) = if disabled {
+        (&config.ferium.disabled, "disabled")
+    } else {
+        (&config.ferium.mods, "mods")
+    };
+
+    let names = mods
+        .iter()
+        .filter(|mod_| mod_.has_tag(tag))
+        .map(|mod_| mod_.identifier.display_name())
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return Err(ModError::NotTagged(tag.to_string(), list.to_string()));
+    }
+
+    Ok(names)
+}
+
+/// Add the tags in `tags` that `mod_` does not have yet
+pub fn add_tags(mod_: &mut ModConfig, tags: &[String]) {
+    for tag in tags {
+        if !mod_.has_tag(tag) {
+            mod_.tags.push(tag.clone());
+        }
+    }
+}
+
+/// Tag the mods in `names` with `tag`, whether they are enabled or disabled
+///
+/// Returns the names of the mods that did not have the tag yet
+pub fn tag_mods(config: &mut FerriteConfig, tag: &str, names: Vec<String>) -> Result<Vec<String>> {
+    let mut tagged = Vec::new();
+    for name in names {
+        let mod_ = config
+            .ferium
+            .mods
+            .iter_mut()
+            .chain(config.ferium.disabled.iter_mut())
+            .find(|mod_| mod_matches(mod_, &name))
+            .ok_or(ModError::NotFound(name))?;
+
+        if mod_.has_tag(tag) {
+            println!(
+                "{}",
+                format!("{} is already tagged {tag}", mod_.name).yellow()
+            );
+            continue;
+        }
+        add_tags(mod_, &[tag.to_string()]);
+        tagged.push(mod_.name.clone());
+    }
+
+    if !tagged.is_empty() {
+        println!(
+            "Tagged {} {}",
+            tagged.iter().map(|txt| txt.bold()).display(", "),
+            tag.blue()
+        );
+    }
+
+    Ok(tagged)
+}
+
+/// Remove `tag` from the mods in `names`, or from every mod that has it if `names` is empty
+///
+/// Returns the names of the mods that had the tag
+pub fn untag_mods(
+    config: &mut FerriteConfig,
+    tag: &str,
+    names: Vec<String>,
+) -> Result<Vec<String>> {
+    let mut untagged = Vec::new();
+    let mut untag = |mod_: &mut ModConfig| {
+        let before = mod_.tags.len();
+        mod_.tags.retain(|other| !other.eq_ignore_ascii_case(tag));
+        if mod_.tags.len() < before {
+            untagged.push(mod_.name.clone());
+        } else {
+            println!("{}", format!("{} is not tagged {tag}", mod_.name).yellow());
+        }
+    };
+
+    if names.is_empty() {
+        config
+            .ferium
+            .mods
+            .iter_mut()
+            .chain(config.ferium.disabled.iter_mut())
+            .filter(|mod_| mod_.has_tag(tag))
+            .for_each(untag);
+    } else {
+        for name in names {
+            let mod_ = config
+                .ferium
+                .mods
+                .iter_mut()
+                .chain(config.ferium.disabled.iter_mut())
+                .find(|mod_| mod_matches(mod_, &name))
+                .ok_or(ModError::NotFound(name))?;
+            untag(mod_);
+        }
+    }
+
+    if untagged.is_empty() {
+        println!("{}", format!("No mods are tagged {tag}").yellow());
+    } else {
+        println!(
+            "Untagged {} {}",
+            untagged.iter().map(|txt| txt.bold()).display(", "),
+            tag.blue()
+        );
+    }
+
+    Ok(untagged)
+}
@@ -25,12 +25,15 @@ pub fn run(config: &mut FerriteConfig, script: &str) -> Result<()> {
                 String::from("P7dR8mSH"),
                 ModIdentifier::ModrinthProject(String::from("Aqlf1Shp"), None),
             );
-            config.ferium.mods.push(Mod::new(
-                String::from("Connector Extras"),
-                ModIdentifier::ModrinthProject(String::from("FYpiwiBR"), None),
-                vec![],
-                false,
-            ));
+            config.ferium.mods.push(
+                Mod::new(
+                    String::from("Connector Extras"),
+                    ModIdentifier::ModrinthProject(String::from("FYpiwiBR"), None),
+                    vec![],
+                    false,
+                )
+                .into(),
+            );
             config.ferium.mod_loaders.push(ModLoader::Fabric);
         }
         _ => return Err(ScriptError::InvalidScript(script.to_string())),
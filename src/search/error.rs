@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SearchError {
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Modrinth API error: {0}")]
+    ModrinthApi(#[from] ferinth::Error),
+
+    #[error("Searching CurseForge needs an API key, add `curseforge_api_key` to the key store")]
+    MissingApiKey,
+
+    #[error("None of the mod loaders {0} can be searched for on {1}")]
+    UnsupportedLoaders(String, String),
+
+    #[error("User cancelled selection")]
+    Cancelled,
+
+    #[error("Inquire error: {0}")]
+    Inquire(String),
+}
+
+impl From<inquire::InquireError> for SearchError {
+    fn from(e: inquire::InquireError) -> Self {
+        match e {
+            inquire::InquireError::OperationCanceled
+            | inquire::InquireError::OperationInterrupted => SearchError::Cancelled,
+            _ => SearchError::Inquire(e.to_string()),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, SearchError>;
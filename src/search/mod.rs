@@ -0,0 +1,293 @@
+mod error;
+mod structs;
+
+pub use error::{Result, SearchError};
+
+use crate::config::{FerriteConfig, api_key};
+use crate::lock::Platform;
+use colored::Colorize as _;
+use ferinth::structures::{
+    project::ProjectSupportRange,
+    search::{Facet, Sort},
+};
+use inquire::MultiSelect;
+use libium::{
+    MODRINTH_API,
+    config::structs::{ModIdentifier, ModLoader},
+    iter_ext::IterExt as _,
+};
+use serde::Serialize;
+use std::{cmp::Reverse, fmt, sync::LazyLock};
+use structs::CurseForgeSearch;
+
+/// Number of results fetched from each platform
+const LIMIT: usize = 10;
+
+/// CurseForge's IDs of Minecraft and of its mods category
+const CURSEFORGE_MINECRAFT: &str = "432";
+const CURSEFORGE_MODS: &str = "6";
+
+/// Client for searching CurseForge, which furse has no call for
+static CURSEFORGE: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .user_agent(user_agent())
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SearchPlatform {
+    Modrinth,
+    #[value(name = "curseforge")]
+    CurseForge,
+}
+
+/// A project found on one of the platforms
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub platform: Platform,
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub author: String,
+    pub downloads: u64,
+    /// Whether the project is needed on the client, `None` if the platform does not say
+    pub client_side: Option<String>,
+    /// Whether the project is needed on the server, `None` if the platform does not say
+    pub server_side: Option<String>,
+    #[serde(skip)]
+    pub identifier: ModIdentifier,
+}
+
+impl fmt::Display for SearchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let platform = match self.platform {
+            Platform::Modrinth => "MR",
+            Platform::CurseForge => "CF",
+            Platform::GitHub => "GH",
+        };
+        let sides = match (&self.server_side, &self.client_side) {
+            (Some(server), Some(client)) => format!("server {server}, client {client}"),
+            _ => "sides unknown".to_string(),
+        };
+        write!(
+            f,
+            "{platform} {:30}  {:>6} downloads  {:36}  by {}",
+            self.name,
+            compact(self.downloads),
+            sides,
+            self.author
+        )
+    }
+}
+
+/// Search Modrinth and CurseForge for `query`, or only `platform`
+///
+/// Only projects for the config's game versions and mod loaders are returned, most downloaded
+/// first. CurseForge is skipped with a warning if there is no API key for it.
+pub async fn search(
+    query: &str,
+    config: &FerriteConfig,
+    platform: Option<SearchPlatform>,
+) -> Result<Vec<SearchResult>> {
+    let mut results = Vec::new();
+
+    if platform != Some(SearchPlatform::CurseForge) {
+        results.extend(search_modrinth(query, config).await?);
+    }
+    if platform != Some(SearchPlatform::Modrinth) {
+        let loaders = config
+            .ferium
+            .mod_loaders
+            .iter()
+            .filter_map(curseforge_loader)
+            .collect::<Vec<_>>();
+
+        match (api_key("curseforge_api_key"), loaders.is_empty()) {
+            (Some(api_key), false) => {
+                results.extend(search_curseforge(query, config, &loaders, &api_key).await?)
+            }
+            (None, _) if platform.is_some() => return Err(SearchError::MissingApiKey),
+            (_, true) if platform.is_some() => {
+                return Err(SearchError::UnsupportedLoaders(
+                    config.ferium.mod_loaders.iter().display(", ").to_string(),
+                    "CurseForge".to_string(),
+                ));
+            }
+            (None, _) => eprintln!(
+                "{}",
+                "Not searching CurseForge, there is no `curseforge_api_key` in the key store"
+                    .yellow()
+            ),
+            (_, true) => {}
+        }
+    }
+
+    results.sort_by_key(|result| Reverse(result.downloads));
+    Ok(results)
+}
+
+async fn search_modrinth(query: &str, config: &FerriteConfig) -> Result<Vec<SearchResult>> {
+    // Facets in the same list are OR-ed, the lists are AND-ed
+    let project_type = if config.ferium.mod_loaders.contains(&ModLoader::Velocity) {
+        "plugin"
+    } else {
+        "mod"
+    };
+    let facets = vec![
+        config
+            .ferium
+            .mod_loaders
+            .iter()
+            .map(|loader| Facet::Categories(modrinth_loader(loader).to_string()))
+            .collect::<Vec<_>>(),
+        config
+            .ferium
+            .game_versions
+            .iter()
+            .map(|version| Facet::Versions(version.clone()))
+            .collect(),
+        vec![Facet::Custom {
+            _type: "project_type".to_string(),
+            operation: ":".to_string(),
+            value: project_type.to_string(),
+        }],
+    ];
+
+    let response = MODRINTH_API
+        .search_paged(query, Sort::Downloads, LIMIT, 0, facets)
+        .await?;
+
+    Ok(response
+        .hits
+        .into_iter()
+        .map(|hit| SearchResult {
+            platform: Platform::Modrinth,
+            identifier: ModIdentifier::ModrinthProject(hit.project_id.clone(), None),
+            slug: hit.slug.unwrap_or_else(|| hit.project_id.clone()),
+            id: hit.project_id,
+            name: hit.title,
+            description: hit.description,
+            author: hit.author,
+            downloads: hit.downloads as u64,
+            client_side: Some(support(hit.client_side).to_string()),
+            server_side: Some(support(hit.server_side).to_string()),
+        })
+        .collect())
+}
+
+async fn search_curseforge(
+    query: &str,
+    config: &FerriteConfig,
+    loaders: &[u8],
+    api_key: &str,
+) -> Result<Vec<SearchResult>> {
+    let response = CURSEFORGE
+        .get("https://api.curseforge.com/v1/mods/search")
+        .header("x-api-key", api_key)
+        .query(&[
+            ("gameId", CURSEFORGE_MINECRAFT.to_string()),
+            ("classId", CURSEFORGE_MODS.to_string()),
+            ("searchFilter", query.to_string()),
+            (
+                "gameVersions",
+                serde_json::to_string(&config.ferium.game_versions).unwrap_or_default(),
+            ),
+            (
+                "modLoaderTypes",
+                serde_json::to_string(loaders).unwrap_or_default(),
+            ),
+            // Most popular first
+            ("sortField", "2".to_string()),
+            ("sortOrder", "desc".to_string()),
+            ("pageSize", LIMIT.to_string()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<CurseForgeSearch>()
+        .await?;
+
+    Ok(response
+        .data
+        .into_iter()
+        .map(|mod_| SearchResult {
+            platform: Platform::CurseForge,
+            identifier: ModIdentifier::CurseForgeProject(mod_.id, None),
+            id: mod_.id.to_string(),
+            slug: mod_.slug,
+            name: mod_.name,
+            description: mod_.summary,
+            author: mod_
+                .authors
+                .into_iter()
+                .map(|author| author.name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            downloads: mod_.download_count as u64,
+            client_side: None,
+            server_side: None,
+        })
+        .collect())
+}
+
+/// Let the user pick which of `results` to add
+pub fn select(results: &[SearchResult]) -> Result<Vec<ModIdentifier>> {
+    let options = results.iter().map(ToString::to_string).collect_vec();
+    Ok(MultiSelect::new("Select mods to add", options)
+        .raw_prompt_skippable()?
+        .unwrap_or_default()
+        .iter()
+        .map(|option| results[option.index].identifier.clone())
+        .collect())
+}
+
+/// The category Modrinth lists projects for `loader` under
+pub fn modrinth_loader(loader: &ModLoader) -> &'static str {
+    match loader {
+        ModLoader::Quilt => "quilt",
+        ModLoader::Fabric => "fabric",
+        ModLoader::Forge => "forge",
+        ModLoader::NeoForge => "neoforge",
+        ModLoader::Velocity => "velocity",
+    }
+}
+
+/// How Modrinth describes whether a project is needed on a side
+pub fn support(range: ProjectSupportRange) -> &'static str {
+    match range {
+        ProjectSupportRange::Required => "required",
+        ProjectSupportRange::Optional => "optional",
+        ProjectSupportRange::Unsupported => "unsupported",
+        ProjectSupportRange::Unknown => "unknown",
+    }
+}
+
+/// CurseForge's ID of `loader`, `None` if CurseForge does not have projects for it
+fn curseforge_loader(loader: &ModLoader) -> Option<u8> {
+    match loader {
+        ModLoader::Forge => Some(1),
+        ModLoader::Fabric => Some(4),
+        ModLoader::Quilt => Some(5),
+        ModLoader::NeoForge => Some(6),
+        ModLoader::Velocity => None,
+    }
+}
+
+fn user_agent() -> String {
+    format!(
+        "ferrite/{} (github.com/septechx/ferrite)",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Format a download count like 12.3M
+pub fn compact(count: u64) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", count as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1}M", count as f64 / 1e6),
+        _ => format!("{:.1}B", count as f64 / 1e9),
+    }
+}
@@ -0,0 +1,22 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct CurseForgeSearch {
+    pub data: Vec<CurseForgeMod>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMod {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub summary: String,
+    pub download_count: f64,
+    pub authors: Vec<CurseForgeAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CurseForgeAuthor {
+    pub name: String,
+}
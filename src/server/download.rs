@@ -2,8 +2,10 @@ use super::error::{Result, ServerError};
 use reqwest::header::CONTENT_DISPOSITION;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 
-pub async fn download_file(url: &str) -> Result<String> {
+/// Download `url` to `dir`, returning the name of the file
+pub async fn download_file(url: &str, dir: &Path) -> Result<String> {
     let response = reqwest::get(url).await?;
     let content_disposition = response
         .headers()
@@ -24,17 +26,18 @@ pub async fn download_file(url: &str) -> Result<String> {
         format!("server-{}.jar", blake3::hash(&bytes))
     };
 
-    let mut file = File::create(&filename)?;
+    let mut file = File::create(dir.join(&filename))?;
     file.write_all(&bytes)?;
     Ok(filename)
 }
 
 pub async fn download_file_with_progress(
     url: &str,
+    dir: &Path,
     progress_bar: &indicatif::ProgressBar,
 ) -> Result<String> {
     progress_bar.set_message(format!("Downloading {url}"));
-    let filename = download_file(url).await?;
+    let filename = download_file(url, dir).await?;
     progress_bar.set_message(format!("Downloaded {filename}"));
     Ok(filename)
 }
@@ -3,16 +3,25 @@ use super::{ServerInstallation, download_file_with_progress, structs::*};
 use colored::Colorize;
 use indicatif::ProgressBar;
 use libium::iter_ext::IterExt;
-use std::{collections::HashMap, fs, process::Command};
+use std::{collections::HashMap, fs, path::Path, process::Command};
 
 pub trait Installer {
-    async fn install(game_version: &str, progress_bar: &ProgressBar) -> Result<ServerInstallation>;
+    /// Install the server into `dir`, the executable is returned relative to it
+    async fn install(
+        game_version: &str,
+        dir: &Path,
+        progress_bar: &ProgressBar,
+    ) -> Result<ServerInstallation>;
 }
 
 pub struct FabricInstaller;
 
 impl Installer for FabricInstaller {
-    async fn install(game_version: &str, progress_bar: &ProgressBar) -> Result<ServerInstallation> {
+    async fn install(
+        game_version: &str,
+        dir: &Path,
+        progress_bar: &ProgressBar,
+    ) -> Result<ServerInstallation> {
         progress_bar.set_message(format!(
             "Fetching Fabric loader versions for {}",
             game_version.green()
@@ -31,7 +40,7 @@ impl Installer for FabricInstaller {
             "https://meta.fabricmc.net/v2/versions/loader/{game_version}/{fabric_version}/{launcher_version}/server/jar",
         );
 
-        let filename = download_file_with_progress(&url, progress_bar).await?;
+        let filename = download_file_with_progress(&url, dir, progress_bar).await?;
 
         progress_bar.finish_with_message(format!(
             "✓ Successfully downloaded server jar for {} ({})",
@@ -49,7 +58,11 @@ impl Installer for FabricInstaller {
 pub struct ForgeInstaller;
 
 impl Installer for ForgeInstaller {
-    async fn install(game_version: &str, progress_bar: &ProgressBar) -> Result<ServerInstallation> {
+    async fn install(
+        game_version: &str,
+        dir: &Path,
+        progress_bar: &ProgressBar,
+    ) -> Result<ServerInstallation> {
         progress_bar.set_message(format!(
             "Fetching Forge loader versions for {}",
             game_version.green()
@@ -67,7 +80,7 @@ impl Installer for ForgeInstaller {
             "https://maven.minecraftforge.net/net/minecraftforge/forge/{forge_version}/forge-{forge_version}-installer.jar",
         );
 
-        let installer_filename = download_file_with_progress(&url, progress_bar).await?;
+        let installer_filename = download_file_with_progress(&url, dir, progress_bar).await?;
 
         progress_bar.set_message(format!(
             "Installing Forge server ({} / {})",
@@ -76,13 +89,14 @@ impl Installer for ForgeInstaller {
         ));
 
         Command::new("java")
+            .current_dir(dir)
             .arg("-jar")
             .arg(&installer_filename)
             .arg("--installServer")
             .output()?;
 
-        fs::remove_file(&installer_filename)?;
-        fs::remove_file(format!("{}.log", &installer_filename))?;
+        fs::remove_file(dir.join(&installer_filename))?;
+        fs::remove_file(dir.join(format!("{installer_filename}.log")))?;
 
         progress_bar.finish_with_message(format!(
             "✓ Successfully installed server for {} ({})",
@@ -104,14 +118,18 @@ impl Installer for ForgeInstaller {
 pub struct QuiltInstaller;
 
 impl Installer for QuiltInstaller {
-    async fn install(game_version: &str, progress_bar: &ProgressBar) -> Result<ServerInstallation> {
+    async fn install(
+        game_version: &str,
+        dir: &Path,
+        progress_bar: &ProgressBar,
+    ) -> Result<ServerInstallation> {
         progress_bar.set_message(format!(
             "Downloading Quilt server installer jar ({})",
             game_version.green()
         ));
 
         let url = "https://quiltmc.org/api/v1/download-latest-installer/java-universal";
-        let installer_filename = download_file_with_progress(url, progress_bar).await?;
+        let installer_filename = download_file_with_progress(url, dir, progress_bar).await?;
 
         progress_bar.set_message(format!(
             "Installing Quilt server ({})",
@@ -119,6 +137,7 @@ impl Installer for QuiltInstaller {
         ));
 
         Command::new("java")
+            .current_dir(dir)
             .arg("-jar")
             .arg(&installer_filename)
             .arg("install")
@@ -128,7 +147,7 @@ impl Installer for QuiltInstaller {
             .arg("--install-dir=./")
             .output()?;
 
-        fs::remove_file(&installer_filename)?;
+        fs::remove_file(dir.join(&installer_filename))?;
 
         progress_bar.finish_with_message(format!(
             "✓ Successfully installed server for {} ({})",
@@ -146,7 +165,11 @@ impl Installer for QuiltInstaller {
 pub struct NeoForgeInstaller;
 
 impl Installer for NeoForgeInstaller {
-    async fn install(game_version: &str, progress_bar: &ProgressBar) -> Result<ServerInstallation> {
+    async fn install(
+        game_version: &str,
+        dir: &Path,
+        progress_bar: &ProgressBar,
+    ) -> Result<ServerInstallation> {
         progress_bar.set_message(format!(
             "Fetching NeoForge loader versions for {}",
             game_version.green()
@@ -164,7 +187,7 @@ impl Installer for NeoForgeInstaller {
             "https://maven.neoforged.net/releases/net/neoforged/neoforge/{neoforge_version}/neoforge-{neoforge_version}-installer.jar",
         );
 
-        let installer_filename = download_file_with_progress(&url, progress_bar).await?;
+        let installer_filename = download_file_with_progress(&url, dir, progress_bar).await?;
 
         progress_bar.set_message(format!(
             "Installing NeoForge server ({} / {})",
@@ -173,13 +196,14 @@ impl Installer for NeoForgeInstaller {
         ));
 
         Command::new("java")
+            .current_dir(dir)
             .arg("-jar")
             .arg(&installer_filename)
             .arg("--installServer")
             .output()?;
 
-        fs::remove_file(&installer_filename)?;
-        fs::remove_file(format!("{}.log", &installer_filename))?;
+        fs::remove_file(dir.join(&installer_filename))?;
+        fs::remove_file(dir.join(format!("{installer_filename}.log")))?;
 
         progress_bar.finish_with_message(format!(
             "✓ Successfully installed server for {} ({})",
@@ -203,6 +227,7 @@ pub struct VelocityInstaller;
 impl Installer for VelocityInstaller {
     async fn install(
         _game_version: &str,
+        dir: &Path,
         progress_bar: &ProgressBar,
     ) -> Result<ServerInstallation> {
         progress_bar.set_message("Fetching Velocity proxy versions");
@@ -242,7 +267,7 @@ impl Installer for VelocityInstaller {
             .url
             .clone();
 
-        let filename = download_file_with_progress(&download_url, progress_bar).await?;
+        let filename = download_file_with_progress(&download_url, dir, progress_bar).await?;
 
         progress_bar.finish_with_message(format!(
             "✓ Successfully downloaded proxy jar for {} ({})",
@@ -9,26 +9,29 @@ pub use installers::*;
 
 use indicatif::ProgressBar;
 use libium::config::structs::ModLoader;
+use std::path::Path;
 
 pub struct ServerInstallation {
     pub executable: String,
     pub wrapper: String,
 }
 
+/// Download or install the server for `game_version` and `mod_loader` into `dir`
 pub async fn get_server_jar(
     game_version: &str,
     mod_loader: &ModLoader,
+    dir: &Path,
 ) -> Result<ServerInstallation> {
     let progress_bar = create_progress_bar(&format!(
         "Downloading server jar for {game_version} ({mod_loader})"
     ));
 
     match mod_loader {
-        ModLoader::Fabric => FabricInstaller::install(game_version, &progress_bar).await,
-        ModLoader::Forge => ForgeInstaller::install(game_version, &progress_bar).await,
-        ModLoader::Quilt => QuiltInstaller::install(game_version, &progress_bar).await,
-        ModLoader::NeoForge => NeoForgeInstaller::install(game_version, &progress_bar).await,
-        ModLoader::Velocity => VelocityInstaller::install(game_version, &progress_bar).await,
+        ModLoader::Fabric => FabricInstaller::install(game_version, dir, &progress_bar).await,
+        ModLoader::Forge => ForgeInstaller::install(game_version, dir, &progress_bar).await,
+        ModLoader::Quilt => QuiltInstaller::install(game_version, dir, &progress_bar).await,
+        ModLoader::NeoForge => NeoForgeInstaller::install(game_version, dir, &progress_bar).await,
+        ModLoader::Velocity => VelocityInstaller::install(game_version, dir, &progress_bar).await,
     }
 }
 
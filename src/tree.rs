@@ -0,0 +1,451 @@
+use crate::lock::{LockedMod, Lockfile, Platform, SkippedMod};
+use colored::Colorize as _;
+use libium::config::structs::ModIdentifier;
+use serde::Serialize;
+use std::{collections::HashMap, fmt::Write as _};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TreeFormat {
+    Text,
+    Dot,
+    Json,
+}
+
+/// A locked file in the dependency graph
+#[derive(Debug, Serialize)]
+pub struct Node {
+    /// The project's platform and ID, see `node_id`
+    pub id: String,
+    pub name: String,
+    pub platform: Platform,
+    pub filename: String,
+    /// Whether it is only installed because other mods depend on it
+    pub dependency: bool,
+    /// How many locked files depend on it
+    pub required_by: usize,
+}
+
+impl Node {
+    /// The project's ID without its platform, as it is written in the config
+    pub fn project(&self) -> &str {
+        self.id
+            .split_once(':')
+            .map_or(self.id.as_str(), |(_, project)| project)
+    }
+}
+
+/// One file depending on another
+#[derive(Debug, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    /// The project `from` asked for, if an override replaced it with `to`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaces: Option<String>,
+}
+
+/// The dependency graph recorded in ferrite.lock
+#[derive(Debug, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    /// Projects that were resolved but not installed, edges can point to them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedMod>,
+}
+
+impl Graph {
+    pub fn new(lockfile: &Lockfile) -> Self {
+        let edges = lockfile
+            .mods
+            .iter()
+            .flat_map(|locked| {
+                locked.dependencies.iter().map(|dependency| Edge {
+                    from: node_id(&locked.identifier),
+                    to: node_id(&dependency.identifier),
+                    replaces: dependency
+                        .replaces
+                        .as_ref()
+                        .map(ModIdentifier::display_name),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut required_by = HashMap::new();
+        for edge in &edges {
+            *required_by.entry(edge.to.as_str()).or_insert(0) += 1;
+        }
+        let nodes = lockfile
+            .mods
+            .iter()
+            .map(|locked| {
+                let id = node_id(&locked.identifier);
+                Node {
+                    required_by: required_by.get(id.as_str()).copied().unwrap_or_default(),
+                    name: display_name(locked),
+                    platform: locked.platform,
+                    filename: locked.filename.clone(),
+                    dependency: locked.dependency,
+                    id,
+                }
+            })
+            .collect();
+
+        Self {
+            nodes,
+            edges,
+            skipped: lockfile.skipped.clone(),
+        }
+    }
+
+    /// Whether ferrite.lock records which file needs which dependency, older locks do not
+    pub fn recorded(&self) -> bool {
+        !self.edges.is_empty() || !self.nodes.iter().any(|node| node.dependency)
+    }
+
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    fn skipped(&self, id: &str) -> Option<&SkippedMod> {
+        self.skipped
+            .iter()
+            .find(|skipped| node_id(&skipped.identifier) == id)
+    }
+
+    /// Every chain of dependencies from a mod in the config to `id`, each ordered from the mod
+    pub fn chains<'a>(&'a self, id: &'a str) -> Vec<Vec<&'a Edge>> {
+        let mut chains = Vec::new();
+        self.walk_up(id, &mut vec![id], &mut Vec::new(), &mut chains);
+        chains
+    }
+
+    fn walk_up<'a>(
+        &'a self,
+        id: &str,
+        seen: &mut Vec<&'a str>,
+        path: &mut Vec<&'a Edge>,
+        chains: &mut Vec<Vec<&'a Edge>>,
+    ) {
+        for edge in self.edges.iter().filter(|edge| edge.to == id) {
+            if seen.contains(&edge.from.as_str()) {
+                continue;
+            }
+
+            path.push(edge);
+            if self.node(&edge.from).is_some_and(|node| !node.dependency) {
+                chains.push(path.iter().rev().copied().collect());
+            } else {
+                seen.push(&edge.from);
+                self.walk_up(&edge.from, seen, path, chains);
+                seen.pop();
+            }
+            path.pop();
+        }
+    }
+
+    fn children(&self, id: &str) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+}
+
+/// The ID of `identifier`'s project in the graph, like `modrinth:AANobbMI`
+///
+/// Display names alone are not unique, a CurseForge ID could also be a Modrinth ID
+pub fn node_id(identifier: &ModIdentifier) -> String {
+    let platform = match identifier {
+        ModIdentifier::ModrinthProject(..) => "modrinth",
+        ModIdentifier::CurseForgeProject(..) => "curseforge",
+        ModIdentifier::GitHubRepository(..) => "github",
+    };
+    format!("{platform}:{}", identifier.display_name())
+}
+
+/// The name of a locked file, without the prefix dependencies are locked with
+pub fn display_name(locked: &LockedMod) -> String {
+    locked
+        .name
+        .strip_prefix("Dependency: ")
+        .unwrap_or(&locked.name)
+        .to_string()
+}
+
+/// The mods in the config with the dependencies they pulled in, recursively
+pub fn text(graph: &Graph) -> String {
+    let mut text = String::new();
+    for node in graph.nodes.iter().filter(|node| !node.dependency) {
+        writeln!(text, "{}  {}", node.name.bold(), node.filename.dimmed()).ok();
+        write_children(graph, &node.id, "", &mut vec![node.id.as_str()], &mut text);
+    }
+
+    if !graph.recorded() {
+        writeln!(
+            text,
+            "\n{}",
+            "ferrite.lock does not record the dependencies yet, run `ferrite upgrade`".yellow()
+        )
+        .ok();
+    }
+    text
+}
+
+fn write_children<'a>(
+    graph: &'a Graph,
+    id: &str,
+    prefix: &str,
+    path: &mut Vec<&'a str>,
+    text: &mut String,
+) {
+    let children = graph.children(id).collect::<Vec<_>>();
+    for (i, edge) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        let mut line = match (graph.node(&edge.to), graph.skipped(&edge.to)) {
+            (Some(node), _) => format!("{}  {}", node.name, node.filename.dimmed()),
+            (None, Some(skipped)) => format!(
+                "{}  {}",
+                skipped.name,
+                format!("(skipped, {})", skipped.reason).dimmed()
+            ),
+            (None, None) => format!("{}  {}", edge.to, "(not locked)".red()),
+        };
+        if let Some(replaces) = &edge.replaces {
+            line = format!("{line}  {}", format!("overrides {replaces}").cyan());
+        }
+        if let Some(node) = graph.node(&edge.to)
+            && node.required_by > 1
+        {
+            line = format!(
+                "{line}  {}",
+                format!("shared by {}", node.required_by).yellow()
+            );
+        }
+
+        // Dependency cycles are cut where they close
+        if path.contains(&edge.to.as_str()) {
+            writeln!(text, "{prefix}{branch}{line}  {}", "(cycle)".dimmed()).ok();
+            continue;
+        }
+        writeln!(text, "{prefix}{branch}{line}").ok();
+
+        path.push(&edge.to);
+        write_children(graph, &edge.to, &format!("{prefix}{indent}"), path, text);
+        path.pop();
+    }
+}
+
+/// Render the graph in Graphviz's DOT language
+pub fn dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph ferrite {\n    rankdir=LR;\n");
+
+    for node in &graph.nodes {
+        let mut attributes = vec![format!("label={}", quote(&node.name))];
+        if node.dependency {
+            attributes.push("shape=box".to_string());
+        }
+        if node.required_by > 1 {
+            attributes.push("color=orange".to_string());
+        }
+        writeln!(dot, "    {} [{}];", quote(&node.id), attributes.join(", ")).ok();
+    }
+    for edge in &graph.edges {
+        let (from, to) = (quote(&edge.from), quote(&edge.to));
+        match &edge.replaces {
+            Some(replaces) => writeln!(
+                dot,
+                "    {from} -> {to} [label={}, style=dashed];",
+                quote(&format!("overrides {replaces}"))
+            ),
+            None => writeln!(dot, "    {from} -> {to};"),
+        }
+        .ok();
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// `value` as a quoted DOT string
+///
+/// Only `"` has to be escaped, but a backslash would start an escape sequence in labels and
+/// a line break would end up in the rendered label
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graph, dot, quote, text};
+    use crate::lock::{
+        Lockfile, SkippedMod,
+        fixtures::{locked, lockfile, modrinth},
+    };
+    use libium::config::structs::ModIdentifier;
+    use serde_json::json;
+
+    /// `text` without the colours it adds when writing to a terminal
+    fn plain(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    /// Two mods sharing Fabric API, one through an override, a dependency that shares its ID
+    /// with a CurseForge project and depends back on Sodium, and a skipped dependency
+    fn sample() -> Lockfile {
+        let mut quotes = locked(
+            "Quotes \"Mod\"",
+            ModIdentifier::CurseForgeProject(238222, None),
+            &[modrinth("P7dR8mSH")],
+        );
+        quotes.dependencies[0].replaces = Some(ModIdentifier::CurseForgeProject(306612, None));
+
+        lockfile(
+            vec![
+                locked(
+                    "Sodium",
+                    modrinth("AANobbMI"),
+                    &[
+                        modrinth("P7dR8mSH"),
+                        modrinth("238222"),
+                        modrinth("YL57xq9U"),
+                    ],
+                ),
+                quotes,
+                locked("Dependency: Fabric API", modrinth("P7dR8mSH"), &[]),
+                locked(
+                    "Dependency: Lib",
+                    modrinth("238222"),
+                    &[modrinth("AANobbMI")],
+                ),
+            ],
+            vec![SkippedMod {
+                name: "Iris".to_string(),
+                identifier: modrinth("YL57xq9U"),
+                dependency: true,
+                reason: "client-only".to_string(),
+            }],
+        )
+    }
+
+    #[test]
+    fn keeps_projects_with_the_same_id_apart() {
+        let graph = Graph::new(&sample());
+
+        assert_eq!(
+            serde_json::to_value(&graph.nodes).unwrap(),
+            json!([
+                {
+                    "id": "modrinth:AANobbMI",
+                    "name": "Sodium",
+                    "platform": "Modrinth",
+                    "filename": "sodium.jar",
+                    "dependency": false,
+                    "required_by": 1
+                },
+                {
+                    "id": "curseforge:238222",
+                    "name": "Quotes \"Mod\"",
+                    "platform": "CurseForge",
+                    "filename": "quotes-mod.jar",
+                    "dependency": false,
+                    "required_by": 0
+                },
+                {
+                    "id": "modrinth:P7dR8mSH",
+                    "name": "Fabric API",
+                    "platform": "Modrinth",
+                    "filename": "fabric-api.jar",
+                    "dependency": true,
+                    "required_by": 2
+                },
+                {
+                    "id": "modrinth:238222",
+                    "name": "Lib",
+                    "platform": "Modrinth",
+                    "filename": "lib.jar",
+                    "dependency": true,
+                    "required_by": 1
+                }
+            ])
+        );
+        assert_eq!(
+            serde_json::to_value(&graph.edges).unwrap(),
+            json!([
+                { "from": "modrinth:AANobbMI", "to": "modrinth:P7dR8mSH" },
+                { "from": "modrinth:AANobbMI", "to": "modrinth:238222" },
+                { "from": "modrinth:AANobbMI", "to": "modrinth:YL57xq9U" },
+                {
+                    "from": "curseforge:238222",
+                    "to": "modrinth:P7dR8mSH",
+                    "replaces": "306612"
+                },
+                { "from": "modrinth:238222", "to": "modrinth:AANobbMI" }
+            ])
+        );
+    }
+
+    #[test]
+    fn renders_text() {
+        assert_eq!(
+            plain(&text(&Graph::new(&sample()))),
+            r#"Sodium  sodium.jar
+├── Fabric API  fabric-api.jar  shared by 2
+├── Lib  lib.jar
+│   └── Sodium  sodium.jar  (cycle)
+└── Iris  (skipped, client-only)
+Quotes "Mod"  quotes-mod.jar
+└── Fabric API  fabric-api.jar  overrides 306612  shared by 2
+"#
+        );
+    }
+
+    #[test]
+    fn renders_dot() {
+        assert_eq!(
+            dot(&Graph::new(&sample())),
+            r#"digraph ferrite {
+    rankdir=LR;
+    "modrinth:AANobbMI" [label="Sodium"];
+    "curseforge:238222" [label="Quotes \"Mod\""];
+    "modrinth:P7dR8mSH" [label="Fabric API", shape=box, color=orange];
+    "modrinth:238222" [label="Lib", shape=box];
+    "modrinth:AANobbMI" -> "modrinth:P7dR8mSH";
+    "modrinth:AANobbMI" -> "modrinth:238222";
+    "modrinth:AANobbMI" -> "modrinth:YL57xq9U";
+    "curseforge:238222" -> "modrinth:P7dR8mSH" [label="overrides 306612", style=dashed];
+    "modrinth:238222" -> "modrinth:AANobbMI";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn escapes_dot_strings() {
+        assert_eq!(quote("a\\b\n\"c\""), r#""a\\b\n\"c\"""#);
+    }
+}
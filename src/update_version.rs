@@ -317,7 +317,7 @@ pub async fn upgrade_version(
     config.ferium.game_versions = vec![target_version.clone()];
 
     println!("{} Downloading new server jar...", "●".cyan());
-    let server_installation = get_server_jar(&target_version, loader).await?;
+    let server_installation = get_server_jar(&target_version, loader, config.dir()).await?;
     config.server.executable = server_installation.executable;
     config.server.wrapper = server_installation.wrapper;
 
@@ -325,7 +325,8 @@ pub async fn upgrade_version(
     println!("{} Configuration updated", "✓".green());
 
     println!("\n{} Upgrading mods...", "●".cyan().bold());
-    upgrade::upgrade(&profile, true, &config.ferium.overrides).await?;
+    // The profile checked above still has the old game version
+    upgrade::upgrade(&config.clone().into(), true, config).await?;
 
     println!("\n{}", "✓ Upgrade complete!".green().bold());
 
@@ -1,15 +1,17 @@
 use super::error::{Result, UpgradeError};
+use super::verify::verify;
+use crate::lock::LockedMod;
 use colored::Colorize as _;
 use fs_extra::{
     dir::{CopyOptions as DirCopyOptions, copy as copy_dir},
     file::{CopyOptions as FileCopyOptions, move_file},
 };
 use indicatif::{ProgressBar, ProgressStyle};
-use libium::{iter_ext::IterExt as _, upgrade::DownloadData};
+use libium::iter_ext::IterExt as _;
 use parking_lot::Mutex;
 use std::{
     ffi::OsString,
-    fs::{copy, create_dir_all, read_dir, remove_file},
+    fs::{copy, create_dir_all, read_dir, remove_dir_all, remove_file, rename},
     path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
@@ -23,10 +25,10 @@ use tokio::task::JoinSet;
 /// - If the file is a `.part` file or if the move failed, the file will be deleted
 pub async fn clean(
     directory: &Path,
-    to_download: &mut Vec<DownloadData>,
+    to_download: &mut Vec<LockedMod>,
     to_install: &mut Vec<(OsString, PathBuf)>,
 ) -> Result<()> {
-    let dupes = find_dupes_by_key(to_download, DownloadData::filename);
+    let dupes = find_dupes_by_key(to_download, |locked| locked.filename.clone());
     if !dupes.is_empty() {
         println!(
             "{}",
@@ -35,7 +37,7 @@ pub async fn clean(
                 dupes.len(),
                 dupes
                     .into_iter()
-                    .map(|i| to_download.swap_remove(i).filename())
+                    .map(|i| to_download.swap_remove(i).filename)
                     .display(", ")
             )
             .yellow()
@@ -53,7 +55,7 @@ pub async fn clean(
             // If it is already downloaded
             if let Some(index) = to_download
                 .iter()
-                .position(|thing| filename == thing.filename())
+                .position(|thing| filename == thing.filename)
             {
                 // Don't download it
                 to_download.swap_remove(index);
@@ -79,9 +81,12 @@ pub async fn clean(
 }
 
 /// Download and install the files in `to_download` and `to_install` to `output_dir`
+///
+/// Files are downloaded to `output_dir`/.download first and are only moved into `output_dir`
+/// once their hash matches the one published by the platform
 pub async fn download(
     output_dir: PathBuf,
-    to_download: Vec<DownloadData>,
+    to_download: Vec<LockedMod>,
     to_install: Vec<(OsString, PathBuf)>,
 ) -> Result<()> {
     let style = ProgressStyle::default_bar()
@@ -92,32 +97,41 @@ pub async fn download(
         .progress_chars("#>-");
 
     let progress_bar = Arc::new(Mutex::new(
-        ProgressBar::new(
-            to_download
-                .iter()
-                .map(|downloadable| downloadable.length as u64)
-                .sum(),
-        )
-        .with_style(style),
+        ProgressBar::new(to_download.iter().map(|locked| locked.size as u64).sum())
+            .with_style(style),
     ));
     progress_bar
         .lock()
         .enable_steady_tick(Duration::from_millis(100));
     let mut tasks = JoinSet::new();
     let client = reqwest::Client::new();
+    let staging_dir = output_dir.join(".download");
+    create_dir_all(&staging_dir)?;
 
-    for downloadable in to_download {
+    for locked in to_download {
         let progress_bar = Arc::clone(&progress_bar);
         let client = client.clone();
         let output_dir = output_dir.clone();
+        let staging_dir = staging_dir.clone();
 
         tasks.spawn(async move {
-            let (length, filename) = downloadable
-                .download(client, &output_dir, |additional| {
+            let (length, filename) = locked
+                .to_download_data()?
+                .download(client, &staging_dir, |additional| {
                     progress_bar.lock().inc(additional as u64);
                 })
                 .await
                 .map_err(|e| UpgradeError::Download(e.to_string()))?;
+
+            let staged = staging_dir.join(&filename);
+            if let Some(hash) = &locked.hash
+                && !verify(&staged, hash)?
+            {
+                remove_file(&staged)?;
+                return Err(UpgradeError::HashMismatch(locked.name, filename));
+            }
+            rename(&staged, output_dir.join(&filename))?;
+
             progress_bar.lock().println(format!(
                 "{} Downloaded  {:>7}  {}",
                 "✓".green(),
@@ -130,7 +144,10 @@ pub async fn download(
             Ok::<(), UpgradeError>(())
         });
     }
-    for res in tasks.join_all().await {
+    let results = tasks.join_all().await;
+    // Anything left in the staging directory is a partial or corrupted download
+    let _ = remove_dir_all(&staging_dir);
+    for res in results {
         res?;
     }
     Arc::try_unwrap(progress_bar)
@@ -29,6 +29,15 @@ pub enum UpgradeError {
 
     #[error("Download error: {0}")]
     Download(String),
+
+    #[error("Hash mismatch for {0} ({1}), the downloaded file was deleted")]
+    HashMismatch(String, String),
+
+    #[error("Failed to fetch metadata for {0}: {1}")]
+    Metadata(String, String),
+
+    #[error("Lockfile error: {0}")]
+    Lock(#[from] crate::lock::LockError),
 }
 
 impl<T> From<std::sync::mpsc::SendError<T>> for UpgradeError {
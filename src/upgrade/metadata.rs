@@ -0,0 +1,119 @@
+use super::error::{Result, UpgradeError};
+use crate::lock::{FileHash, HashAlgorithm};
+use furse::structures::file_structs::HashAlgo;
+use libium::{CURSEFORGE_API, MODRINTH_API, config::structs::ModIdentifier, upgrade::DownloadData};
+
+/// Platform information about a resolved file that `DownloadData` does not carry
+#[derive(Debug, Clone)]
+pub struct FileMetadata {
+    pub version_id: String,
+    pub version: Option<String>,
+    pub hash: Option<FileHash>,
+}
+
+/// Fetch the version ID, version number and hash of `download` from the platform `identifier` belongs to
+///
+/// GitHub releases do not publish hashes, so only the release tag is recorded for them
+pub async fn fetch_metadata(
+    identifier: &ModIdentifier,
+    download: &DownloadData,
+) -> Result<FileMetadata> {
+    let filename = download.filename();
+    let metadata_error = |reason: String| UpgradeError::Metadata(filename.clone(), reason);
+
+    match identifier {
+        ModIdentifier::ModrinthProject(..) => {
+            let version_id = version_id(identifier, download)
+                .ok_or_else(|| metadata_error("unrecognised Modrinth URL".to_string()))?;
+
+            let version = MODRINTH_API
+                .version_get(&version_id)
+                .await
+                .map_err(|e| metadata_error(e.to_string()))?;
+            let number = version.version_number;
+            let hash = version
+                .files
+                .into_iter()
+                .find(|file| file.filename == filename)
+                .map(|file| FileHash {
+                    algorithm: HashAlgorithm::Sha512,
+                    value: file.hashes.sha512,
+                });
+
+            Ok(FileMetadata {
+                version_id,
+                version: Some(number),
+                hash,
+            })
+        }
+        ModIdentifier::CurseForgeProject(mod_id, _) => {
+            let file_id = curseforge_file_id(download)
+                .ok_or_else(|| metadata_error("unrecognised CurseForge URL".to_string()))?;
+
+            let file = CURSEFORGE_API
+                .get_mod_file(*mod_id, file_id)
+                .await
+                .map_err(|e| metadata_error(e.to_string()))?;
+            // Prefer SHA-1, CurseForge only publishes MD5 for some older files
+            let hash = file
+                .hashes
+                .iter()
+                .find(|hash| matches!(hash.algo, HashAlgo::Sha1))
+                .or_else(|| file.hashes.first())
+                .map(|hash| FileHash {
+                    algorithm: match hash.algo {
+                        HashAlgo::Sha1 => HashAlgorithm::Sha1,
+                        HashAlgo::Md5 => HashAlgorithm::Md5,
+                    },
+                    value: hash.value.clone(),
+                });
+
+            Ok(FileMetadata {
+                version_id: file_id.to_string(),
+                version: Some(file.display_name),
+                hash,
+            })
+        }
+        ModIdentifier::GitHubRepository(..) => {
+            let tag = version_id(identifier, download).unwrap_or_default();
+            Ok(FileMetadata {
+                version: Some(tag.clone()),
+                version_id: tag,
+                hash: None,
+            })
+        }
+    }
+}
+
+/// The ID of the version `download` belongs to on the platform of `identifier`, from its URL
+///
+/// For GitHub this is the release tag
+pub fn version_id(identifier: &ModIdentifier, download: &DownloadData) -> Option<String> {
+    let segments = download.download_url.path_segments()?.collect::<Vec<_>>();
+    let after = |segment: &str| {
+        let i = segments.iter().position(|s| *s == segment)?;
+        segments.get(i + 1).map(|s| s.to_string())
+    };
+
+    match identifier {
+        // https://cdn.modrinth.com/data/<project>/versions/<version>/<filename>
+        ModIdentifier::ModrinthProject(..) => after("versions"),
+        ModIdentifier::CurseForgeProject(..) => {
+            curseforge_file_id(download).map(|id| id.to_string())
+        }
+        // https://github.com/<owner>/<repo>/releases/download/<tag>/<filename>
+        ModIdentifier::GitHubRepository(..) => after("download"),
+    }
+}
+
+/// The ID of the CurseForge file `download` is, from its URL
+///
+/// https://edge.forgecdn.net/files/<id / 1000>/<id % 1000>/<filename>
+pub fn curseforge_file_id(download: &DownloadData) -> Option<i32> {
+    let segments = download.download_url.path_segments()?.collect::<Vec<_>>();
+    let i = segments.iter().position(|s| *s == "files")?;
+    let high = segments.get(i + 1)?.parse::<i32>().ok()?;
+    let low = segments.get(i + 2)?.parse::<i32>().ok()?;
+
+    Some(high * 1000 + low)
+}
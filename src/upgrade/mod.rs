@@ -1,6 +1,13 @@
 mod download;
 mod error;
+mod metadata;
+mod outdated;
+mod prune;
 mod resolve;
+mod side;
+mod verify;
 
 pub use error::UpgradeError;
-pub use resolve::upgrade;
+pub use outdated::outdated;
+pub use prune::{prune, prune_orphans};
+pub use resolve::{install_locked, upgrade};
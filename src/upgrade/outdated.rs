@@ -0,0 +1,199 @@
+use super::error::Result;
+use super::metadata::version_id;
+use super::resolve::get_platform_downloadables;
+use crate::config::FerriteConfig;
+use crate::lock::load_lock;
+use colored::Colorize as _;
+use libium::{
+    CURSEFORGE_API, GITHUB_API, MODRINTH_API,
+    config::structs::{ModIdentifier, Profile},
+};
+use std::{collections::HashSet, fs::read_dir};
+use tokio::task::JoinSet;
+
+/// A version of a project as its platform publishes it
+#[derive(Debug, Clone)]
+struct Release {
+    number: String,
+    date: Option<String>,
+    channel: Option<String>,
+}
+
+/// A mod or dependency whose latest compatible file is not the one installed
+#[derive(Debug, Clone)]
+struct Update {
+    name: String,
+    dependency: bool,
+    /// The installed release, `None` if no locked file of the project is installed
+    current: Option<Release>,
+    latest: Release,
+}
+
+/// What `outdated` found
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+    /// How many mods and dependencies can be updated
+    pub updates: usize,
+    /// Whether some mods could not be resolved, so their updates are not counted
+    pub unresolved: bool,
+}
+
+/// Resolve the latest compatible files for `profile` without downloading them, and print the
+/// mods and dependencies whose latest file is not the one in the output directory
+pub async fn outdated(profile: &Profile, config: &FerriteConfig) -> Result<Summary> {
+    let resolution = get_platform_downloadables(profile, false, &config.ferium).await?;
+    let lockfile = load_lock(&config.lock_path())?;
+
+    let mut present = HashSet::new();
+    if profile.output_dir.is_dir() {
+        for file in read_dir(&profile.output_dir)? {
+            present.insert(file?.file_name().to_string_lossy().to_string());
+        }
+    }
+
+    let mut tasks = JoinSet::new();
+    for resolved in resolution.resolved {
+        let filename = resolved.download.filename();
+        if present.contains(&filename) {
+            continue;
+        }
+
+        let id = resolved.mod_.identifier.display_name();
+        let installed = lockfile.as_ref().and_then(|lockfile| {
+            lockfile
+                .mods
+                .iter()
+                .find(|locked| locked.identifier.display_name() == id)
+                .filter(|locked| present.contains(&locked.filename))
+                .cloned()
+        });
+        let latest = version_id(&resolved.mod_.identifier, &resolved.download);
+
+        tasks.spawn(async move {
+            let identifier = &resolved.mod_.identifier;
+            let current = match &installed {
+                Some(locked) => Some(
+                    release(identifier, &locked.version_id)
+                        .await
+                        .unwrap_or_else(|| file_release(&locked.filename)),
+                ),
+                None => None,
+            };
+            let latest = match latest {
+                Some(latest) => release(identifier, &latest).await,
+                None => None,
+            }
+            .unwrap_or_else(|| file_release(&filename));
+
+            Update {
+                name: resolved.mod_.name,
+                dependency: resolved.dependency,
+                current,
+                latest,
+            }
+        });
+    }
+
+    let mut updates = tasks.join_all().await;
+    updates.sort_by(|a, b| {
+        a.dependency
+            .cmp(&b.dependency)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    let pad_len = updates
+        .iter()
+        .map(|update| update.name.len())
+        .max()
+        .unwrap_or(20)
+        .clamp(20, 50);
+    for update in &updates {
+        let current = update.current.as_ref().map_or_else(
+            || "not installed".dimmed().to_string(),
+            |current| current.number.clone(),
+        );
+        println!(
+            "{:pad_len$}  {} → {}  {}  {}",
+            update.name,
+            current,
+            update.latest.number.green(),
+            update.latest.date.as_deref().unwrap_or_default().dimmed(),
+            update
+                .latest
+                .channel
+                .as_deref()
+                .unwrap_or_default()
+                .yellow(),
+        );
+    }
+
+    if updates.is_empty() && !resolution.error {
+        println!("{}", "All mods are up to date".dimmed());
+    }
+
+    Ok(Summary {
+        updates: updates.len(),
+        unresolved: resolution.error,
+    })
+}
+
+/// Fetch the number, release date and channel of the version `version_id` of `identifier`
+///
+/// Returns `None` if the platform could not be asked
+async fn release(identifier: &ModIdentifier, version_id: &str) -> Option<Release> {
+    match identifier {
+        ModIdentifier::ModrinthProject(..) => {
+            let version = MODRINTH_API.version_get(version_id).await.ok()?;
+
+            Some(Release {
+                number: version.version_number,
+                date: Some(version.date_published.date_naive().to_string()),
+                channel: Some(format!("{:?}", version.version_type).to_lowercase()),
+            })
+        }
+        ModIdentifier::CurseForgeProject(mod_id, _) => {
+            let file = CURSEFORGE_API
+                .get_mod_file(*mod_id, version_id.parse().ok()?)
+                .await
+                .ok()?;
+
+            Some(Release {
+                number: file.display_name,
+                date: Some(file.file_date.date_naive().to_string()),
+                channel: Some(format!("{:?}", file.release_type).to_lowercase()),
+            })
+        }
+        ModIdentifier::GitHubRepository((owner, repo), _) => {
+            let release = GITHUB_API
+                .repos(owner, repo)
+                .releases()
+                .get_by_tag(version_id)
+                .await
+                .ok()?;
+
+            Some(Release {
+                number: version_id.to_string(),
+                date: release
+                    .published_at
+                    .map(|published| published.date_naive().to_string()),
+                channel: Some(
+                    if release.prerelease {
+                        "prerelease"
+                    } else {
+                        "release"
+                    }
+                    .to_string(),
+                ),
+            })
+        }
+    }
+}
+
+/// A release known only by its file name
+fn file_release(filename: &str) -> Release {
+    Release {
+        number: filename.to_string(),
+        date: None,
+        channel: None,
+    }
+}
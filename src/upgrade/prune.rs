@@ -0,0 +1,349 @@
+use super::error::Result;
+use crate::lock::{LockError, LockedMod, Lockfile, load_lock};
+use crate::tree::{Graph, display_name, node_id};
+use colored::Colorize as _;
+use libium::config::{
+    filters::ProfileParameters as _,
+    structs::{ModLoader, Profile},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{create_dir_all, read_dir, rename},
+    path::Path,
+};
+
+/// Lock entries that nothing needs anymore now that only `profile.mods` are configured
+///
+/// Every dependency is counted once for each locked file that requires it. Files of mods that
+/// are no longer configured and that nothing requires are released, which releases their
+/// dependencies in turn once their count drops to zero. Dependencies that only require each
+/// other are released along with them. Disabled mods release their dependencies like removed
+/// ones, but their own entries are kept so that enabling them restores their files.
+pub fn orphans(lockfile: &Lockfile, profile: &Profile) -> Vec<LockedMod> {
+    let graph = Graph::new(lockfile);
+    let kept = profile
+        .mods
+        .iter()
+        .map(|mod_| node_id(&mod_.identifier))
+        .collect::<HashSet<_>>();
+    let disabled = profile
+        .disabled
+        .iter()
+        .map(|mod_| node_id(&mod_.identifier))
+        .collect::<HashSet<_>>();
+
+    let mut counts = HashMap::new();
+    for edge in &graph.edges {
+        *counts.entry(edge.to.as_str()).or_insert(0usize) += 1;
+    }
+
+    // Without the recorded edges every dependency would look unused
+    let mut released = graph
+        .nodes
+        .iter()
+        .filter(|node| {
+            !kept.contains(&node.id)
+                && counts.get(node.id.as_str()).is_none_or(|count| *count == 0)
+                && (graph.recorded() || !node.dependency)
+        })
+        .map(|node| node.id.as_str())
+        .collect::<Vec<_>>();
+    let mut queue = released.clone();
+    while let Some(id) = queue.pop() {
+        for edge in graph.edges.iter().filter(|edge| edge.from == id) {
+            let Some(count) = counts.get_mut(edge.to.as_str()) else {
+                continue;
+            };
+            *count = count.saturating_sub(1);
+            if *count == 0 && !kept.contains(&edge.to) && !released.contains(&edge.to.as_str()) {
+                released.push(&edge.to);
+                queue.push(&edge.to);
+            }
+        }
+    }
+
+    // A cycle keeps the counts of its files above zero, so whatever the kept mods do not
+    // reach is released as well
+    if graph.recorded() {
+        let mut reachable = graph
+            .nodes
+            .iter()
+            .filter(|node| kept.contains(&node.id))
+            .map(|node| node.id.as_str())
+            .collect::<Vec<_>>();
+        let mut queue = reachable.clone();
+        while let Some(id) = queue.pop() {
+            for edge in graph.edges.iter().filter(|edge| edge.from == id) {
+                if !reachable.contains(&edge.to.as_str()) {
+                    reachable.push(&edge.to);
+                    queue.push(&edge.to);
+                }
+            }
+        }
+        released.extend(
+            graph
+                .nodes
+                .iter()
+                .map(|node| node.id.as_str())
+                .filter(|id| !reachable.contains(id) && !released.contains(id))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    lockfile
+        .mods
+        .iter()
+        .filter(|locked| {
+            let id = node_id(&locked.identifier);
+            released.contains(&id.as_str()) && !disabled.contains(&id)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Move the files of orphaned lock entries to `.old` and drop them from ferrite.lock, without
+/// resolving anything
+///
+/// Returns the entries that were pruned
+pub fn prune_orphans(profile: &Profile, lock_path: &Path) -> Result<Vec<LockedMod>> {
+    let Some(mut lockfile) = load_lock(lock_path)? else {
+        return Ok(Vec::new());
+    };
+
+    if !Graph::new(&lockfile).recorded() {
+        println!(
+            "{}",
+            "Not pruning dependencies, run `ferrite upgrade` to record them in ferrite.lock"
+                .yellow()
+        );
+    }
+    let orphans = orphans(&lockfile, profile);
+    if orphans.is_empty() {
+        return Ok(orphans);
+    }
+
+    for orphan in &orphans {
+        move_to_old(&profile.output_dir, &orphan.filename)?;
+        println!(
+            "{} Pruned {}  {}",
+            "✓".green(),
+            display_name(orphan).bold(),
+            orphan.filename.dimmed()
+        );
+    }
+    lockfile.mods.retain(|locked| !orphans.contains(locked));
+    lockfile.write(lock_path)?;
+
+    Ok(orphans)
+}
+
+/// Prune orphaned dependencies, then move the jars in the output directory that are neither
+/// locked nor copied from `user/` to `.old`
+pub fn prune(profile: &Profile, lock_path: &Path) -> Result<()> {
+    // Without a lock every jar would look unmanaged
+    if load_lock(lock_path)?.is_none() {
+        return Err(LockError::Missing.into());
+    }
+    let pruned = prune_orphans(profile, lock_path)?;
+    let locked = load_lock(lock_path)?
+        .map(|lockfile| lockfile.mods)
+        .unwrap_or_default();
+
+    let mut user = HashSet::new();
+    let user_dir = profile.output_dir.join("user");
+    if user_dir.is_dir() && profile.filters.mod_loader() != Some(&ModLoader::Quilt) {
+        for file in read_dir(user_dir)? {
+            user.insert(file?.file_name().to_string_lossy().to_string());
+        }
+    }
+
+    let mut unmanaged = Vec::new();
+    if profile.output_dir.is_dir() {
+        for file in read_dir(&profile.output_dir)? {
+            let file = file?;
+            let filename = file.file_name().to_string_lossy().to_string();
+            if file.file_type()?.is_file()
+                && filename.to_lowercase().ends_with(".jar")
+                && !user.contains(&filename)
+                && !locked.iter().any(|locked| locked.filename == filename)
+            {
+                unmanaged.push(filename);
+            }
+        }
+    }
+    unmanaged.sort();
+    for filename in &unmanaged {
+        move_to_old(&profile.output_dir, filename)?;
+        println!(
+            "{} Moved {} to .old, it is not in ferrite.lock",
+            "✓".green(),
+            filename.dimmed()
+        );
+    }
+
+    if pruned.is_empty() && unmanaged.is_empty() {
+        println!("{}", "Nothing to prune".dimmed());
+    }
+
+    Ok(())
+}
+
+fn move_to_old(output_dir: &Path, filename: &str) -> Result<()> {
+    let path = output_dir.join(filename);
+    if path.is_file() {
+        create_dir_all(output_dir.join(".old"))?;
+        rename(path, output_dir.join(".old").join(filename))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::orphans;
+    use crate::lock::{
+        LockedMod, Lockfile,
+        fixtures::{self, lockfile, modrinth},
+    };
+    use crate::tree::display_name;
+    use libium::config::structs::{Mod, ModLoader, Profile};
+    use std::path::PathBuf;
+
+    fn locked(id: &str, dependency: bool, dependencies: &[&str]) -> LockedMod {
+        let name = if dependency {
+            format!("Dependency: {id}")
+        } else {
+            id.to_string()
+        };
+        let dependencies = dependencies
+            .iter()
+            .copied()
+            .map(modrinth)
+            .collect::<Vec<_>>();
+        fixtures::locked(&name, modrinth(id), &dependencies)
+    }
+
+    fn profile(mods: &[&str], disabled: &[&str]) -> Profile {
+        let list = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| Mod::new(id.to_string(), modrinth(id), vec![], false))
+                .collect()
+        };
+        Profile::new_complete(
+            String::from("ferrite"),
+            PathBuf::from("mods"),
+            vec!["1.21.1".to_string()],
+            vec![ModLoader::Fabric],
+            list(mods),
+            list(disabled),
+        )
+    }
+
+    fn orphaned(lockfile: &Lockfile, profile: &Profile) -> Vec<String> {
+        orphans(lockfile, profile)
+            .into_iter()
+            .map(|locked| display_name(&locked))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_shared_dependencies() {
+        let lockfile = lockfile(
+            vec![
+                locked("sodium", false, &["fabric-api"]),
+                locked("lithium", false, &["fabric-api"]),
+                locked("fabric-api", true, &[]),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(orphaned(&lockfile, &profile(&["lithium"], &[])), ["sodium"]);
+        assert_eq!(
+            orphaned(&lockfile, &profile(&[], &[])),
+            ["sodium", "lithium", "fabric-api"]
+        );
+    }
+
+    #[test]
+    fn releases_chains() {
+        let lockfile = lockfile(
+            vec![
+                locked("sodium", false, &["indium"]),
+                locked("indium", true, &["fabric-api"]),
+                locked("fabric-api", true, &[]),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(
+            orphaned(&lockfile, &profile(&[], &[])),
+            ["sodium", "indium", "fabric-api"]
+        );
+        assert!(orphaned(&lockfile, &profile(&["sodium"], &[])).is_empty());
+    }
+
+    #[test]
+    fn releases_overridden_dependencies() {
+        let mut sodium = locked("sodium", false, &["fabric-api"]);
+        sodium.dependencies[0].replaces = Some(modrinth("qsfabric"));
+        let lockfile = lockfile(vec![sodium, locked("fabric-api", true, &[])], Vec::new());
+
+        assert!(orphaned(&lockfile, &profile(&["sodium"], &[])).is_empty());
+        assert_eq!(
+            orphaned(&lockfile, &profile(&[], &[])),
+            ["sodium", "fabric-api"]
+        );
+    }
+
+    #[test]
+    fn releases_cycles() {
+        let lockfile = lockfile(
+            vec![
+                locked("sodium", false, &["indium"]),
+                locked("indium", true, &["fabric-api"]),
+                locked("fabric-api", true, &["indium"]),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(
+            orphaned(&lockfile, &profile(&[], &[])),
+            ["sodium", "indium", "fabric-api"]
+        );
+        assert!(orphaned(&lockfile, &profile(&["sodium"], &[])).is_empty());
+    }
+
+    #[test]
+    fn releases_dependencies_of_disabled_mods() {
+        let lockfile = lockfile(
+            vec![
+                locked("sodium", false, &["indium", "fabric-api"]),
+                locked("lithium", false, &["fabric-api"]),
+                locked("indium", true, &[]),
+                locked("fabric-api", true, &[]),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(
+            orphaned(&lockfile, &profile(&["lithium"], &["sodium"])),
+            ["indium"]
+        );
+        assert_eq!(
+            orphaned(&lockfile, &profile(&[], &["sodium"])),
+            ["lithium", "indium", "fabric-api"]
+        );
+    }
+
+    #[test]
+    fn keeps_unrecorded_dependencies() {
+        let lockfile = lockfile(
+            vec![
+                locked("sodium", false, &[]),
+                locked("fabric-api", true, &[]),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(orphaned(&lockfile, &profile(&[], &[])), ["sodium"]);
+    }
+}
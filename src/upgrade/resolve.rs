@@ -1,5 +1,12 @@
 use super::download::{clean, download};
 use super::error::{Result, UpgradeError};
+use super::metadata::fetch_metadata;
+use super::side::client_only;
+use crate::config::{FeriumConfig, FerriteConfig, Side};
+use crate::lock::{
+    LockedDependency, LockedMod, Lockfile, Platform, ResolvedFor, SkippedMod, load_lock,
+};
+use crate::mods::pinned_version;
 use colored::Colorize as _;
 use indicatif::{ProgressBar, ProgressStyle};
 use libium::{
@@ -11,30 +18,58 @@ use libium::{
 };
 use parking_lot::Mutex;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::{self, read_dir},
-    mem::take,
+    mem::{Discriminant, discriminant, take},
     sync::{Arc, mpsc},
     time::Duration,
 };
 use tokio::task::JoinSet;
 
+/// A file resolved for a mod in the profile or for one of their dependencies
+#[derive(Debug, Clone)]
+pub struct Resolved {
+    pub mod_: Mod,
+    pub dependency: bool,
+    pub download: DownloadData,
+    /// The dependencies of the file, after applying the overrides
+    pub dependencies: Vec<LockedDependency>,
+}
+
+/// The files resolved for the mods in a profile and their dependencies
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub resolved: Vec<Resolved>,
+    /// Mods and dependencies that are not needed on the server
+    pub skipped: Vec<SkippedMod>,
+    /// Whether some mods could not be resolved
+    pub error: bool,
+}
+
+/// What became of a mod or dependency during resolution
+enum Outcome {
+    Resolved(Resolved),
+    Skipped(SkippedMod),
+    Failed,
+}
+
 /// Get the latest compatible downloadable for the mods in `profile`
 ///
-/// If an error occurs with a resolving task, instead of failing immediately,
-/// resolution will continue and the error return flag is set to true.
+/// Mods and dependencies that only run on clients are skipped, unless `side:` in `ferium` says
+/// otherwise. If an error occurs with a resolving task, instead of failing immediately,
+/// resolution will continue and the error flag is set.
 pub async fn get_platform_downloadables(
     profile: &Profile,
     user: bool,
-    overrides: &HashMap<String, ModIdentifier>,
-) -> Result<(Vec<DownloadData>, bool)> {
+    ferium: &FeriumConfig,
+) -> Result<Resolution> {
     let style = ProgressStyle::default_bar()
         .template("{spinner} {elapsed} [{wide_bar:.cyan/blue}] {pos:.cyan}/{len:.blue}")
         .unwrap_or_else(|_| ProgressStyle::default_bar())
         .progress_chars("#>-");
     let progress_bar = Arc::new(Mutex::new(ProgressBar::new(0).with_style(style)));
     let mut tasks = JoinSet::new();
-    let mut done_mods = Vec::new();
+    let mut done_projects = HashSet::new();
     let (mod_sender, mod_rcvr) = mpsc::channel();
 
     // Wrap it again in an Arc so that I can count the references to it,
@@ -54,9 +89,16 @@ pub async fn get_platform_downloadables(
         .max()
         .unwrap_or(20)
         .clamp(20, 50);
+    // Keyed by project so that the sides of configured mods also apply when they are dependencies
+    let sides = ferium
+        .mods
+        .iter()
+        .chain(&ferium.disabled)
+        .filter_map(|mod_| Some((project(&mod_.identifier), mod_.side?)))
+        .collect::<HashMap<_, _>>();
 
     for mod_ in profile.mods.clone() {
-        mod_sender.send(mod_)?;
+        mod_sender.send((mod_, false))?;
     }
 
     let mut initial = true;
@@ -69,49 +111,80 @@ pub async fn get_platform_downloadables(
     // TODO: Fix bug where if mods is empty initial will never be false and this loop will run for
     // ever
     while Arc::strong_count(&mod_sender) > 1 || initial {
-        if let Ok(mod_) = mod_rcvr.try_recv() {
+        if let Ok((mod_, dependency)) = mod_rcvr.try_recv() {
             initial = false;
 
-            if done_mods.contains(&mod_.identifier) {
+            // The mods in the profile are queued before any dependency, so a mod that is pinned
+            // keeps its version when another mod depends on the same project
+            if !done_projects.insert(project(&mod_.identifier)) {
                 continue;
             }
 
-            done_mods.push(mod_.identifier.clone());
             progress_bar.lock().inc_length(1);
 
             let filters = profile.filters.clone();
-            let overrides = overrides.clone();
+            let overrides = ferium.overrides.clone();
+            let side = sides.get(&project(&mod_.identifier)).copied();
             let dep_sender = Arc::clone(&mod_sender);
             let progress_bar = Arc::clone(&progress_bar);
 
             tasks.spawn(async move {
+                if side == Some(Side::Client) {
+                    progress_bar.lock().inc(1);
+                    let reason = "set to `side: Client` in the config".to_string();
+                    return Ok(skip(&progress_bar, mod_, dependency, reason, pad_len));
+                }
+
                 let result = mod_.fetch_download_file(filters).await;
 
                 progress_bar.lock().inc(1);
                 match result {
                     Ok(mut download_file) => {
+                        // Pinned mods resolve to the version they are pinned to
+                        let pinned = match pinned_version(&mod_.identifier) {
+                            Some(version) if !dependency => {
+                                format!("  {}", format!("pinned to {version}").cyan())
+                            }
+                            _ => String::new(),
+                        };
                         progress_bar.lock().println(format!(
-                            "{} {:pad_len$}  {}",
+                            "{} {:pad_len$}  {}{}",
                             "✓".green(),
                             mod_.name,
-                            download_file.filename().dimmed()
+                            download_file.filename().dimmed(),
+                            pinned
                         ));
+                        let mut dependencies = Vec::new();
                         for dep in take(&mut download_file.dependencies) {
                             let override_identifier = dep.display_name();
 
                             let mut identifier = dep;
+                            let mut replaces = None;
                             if let Some(override_) = overrides.get(&override_identifier) {
+                                replaces = Some(identifier);
                                 identifier = override_.clone();
                             };
+                            dependencies.push(LockedDependency {
+                                identifier: identifier.clone(),
+                                replaces,
+                            });
 
-                            dep_sender.send(Mod::new(
-                                format!("Dependency: {}", identifier.display_name()),
-                                identifier,
-                                vec![],
-                                false,
+                            dep_sender.send((
+                                Mod::new(
+                                    format!("Dependency: {}", identifier.display_name()),
+                                    identifier,
+                                    vec![],
+                                    false,
+                                ),
+                                true,
                             ))?;
                         }
-                        Ok(Some(download_file))
+                        Ok(Outcome::Resolved(Resolved {
+                            mod_,
+                            dependency,
+                            download: download_file,
+                            dependencies,
+                        }))
                     }
                     Err(err) => {
                         if let mod_downloadable::Error::ModrinthError(
@@ -126,7 +199,7 @@ pub async fn get_platform_downloadables(
                             "{}",
                             format!("× {:pad_len$}  {err}", mod_.name).red()
                         ));
-                        Ok(None)
+                        Ok(Outcome::Failed)
                     }
                 }
             });
@@ -144,18 +217,229 @@ pub async fn get_platform_downloadables(
         .into_iter()
         .collect::<Result<Vec<_>>>()?;
 
-    let error = tasks.iter().any(Option::is_none);
-    let to_download = tasks.into_iter().flatten().collect();
+    let mut resolution = Resolution {
+        resolved: Vec::new(),
+        skipped: Vec::new(),
+        error: false,
+    };
+    for outcome in tasks {
+        match outcome {
+            Outcome::Resolved(resolved) => resolution.resolved.push(resolved),
+            Outcome::Skipped(skipped) => resolution.skipped.push(skipped),
+            Outcome::Failed => resolution.error = true,
+        }
+    }
+    skip_client_only(&mut resolution, &sides, pad_len).await;
 
-    Ok((to_download, error))
+    Ok(resolution)
 }
 
-pub async fn upgrade(
-    profile: &Profile,
-    user: bool,
-    overrides: &HashMap<String, ModIdentifier>,
-) -> Result<()> {
-    let (mut to_download, error) = get_platform_downloadables(profile, user, overrides).await?;
+/// Move the files that only run on clients to `resolution.skipped`, along with the
+/// dependencies that no file left on the server needs
+///
+/// The platforms are asked about all files at once, except for the projects `side:` is set for
+async fn skip_client_only(
+    resolution: &mut Resolution,
+    sides: &HashMap<(Discriminant<ModIdentifier>, String), Side>,
+    pad_len: usize,
+) {
+    let unchecked = resolution
+        .resolved
+        .iter()
+        .filter(|resolved| !sides.contains_key(&project(&resolved.mod_.identifier)))
+        .collect::<Vec<_>>();
+    let files = unchecked
+        .iter()
+        .map(|resolved| (&resolved.mod_.identifier, &resolved.download))
+        .collect::<Vec<_>>();
+    let mut reasons = unchecked
+        .iter()
+        .zip(client_only(&files).await)
+        .filter_map(|(resolved, reason)| Some((project(&resolved.mod_.identifier), reason?)))
+        .collect::<HashMap<_, _>>();
+    if reasons.is_empty() {
+        return;
+    }
+
+    // Dependencies are kept as long as a mod that is kept still requires them
+    let mut needed = resolution
+        .resolved
+        .iter()
+        .filter(|resolved| !resolved.dependency)
+        .map(|resolved| project(&resolved.mod_.identifier))
+        .filter(|project| !reasons.contains_key(project))
+        .collect::<HashSet<_>>();
+    let mut queue = needed.iter().cloned().collect::<Vec<_>>();
+    while let Some(needed_project) = queue.pop() {
+        let Some(resolved) = resolution
+            .resolved
+            .iter()
+            .find(|resolved| project(&resolved.mod_.identifier) == needed_project)
+        else {
+            continue;
+        };
+        for dependency in &resolved.dependencies {
+            let dependency = project(&dependency.identifier);
+            if !reasons.contains_key(&dependency) && needed.insert(dependency.clone()) {
+                queue.push(dependency);
+            }
+        }
+    }
+    for resolved in &resolution.resolved {
+        let project = project(&resolved.mod_.identifier);
+        if !needed.contains(&project) {
+            reasons
+                .entry(project)
+                .or_insert_with(|| "only client-only mods require it".to_string());
+        }
+    }
+
+    let (skipped, resolved) = take(&mut resolution.resolved)
+        .into_iter()
+        .partition::<Vec<_>, _>(|resolved| {
+            reasons.contains_key(&project(&resolved.mod_.identifier))
+        });
+    resolution.resolved = resolved;
+    for resolved in skipped {
+        let reason = reasons
+            .remove(&project(&resolved.mod_.identifier))
+            .unwrap_or_default();
+        println!("{}", skip_line(&resolved.mod_, &reason, pad_len));
+        resolution.skipped.push(SkippedMod {
+            name: resolved.mod_.name,
+            identifier: resolved.mod_.identifier,
+            dependency: resolved.dependency,
+            reason,
+        });
+    }
+}
+
+/// The project `identifier` refers to, whatever version it is pinned to
+fn project(identifier: &ModIdentifier) -> (Discriminant<ModIdentifier>, String) {
+    (discriminant(identifier), identifier.display_name())
+}
+
+fn skip(
+    progress_bar: &Mutex<ProgressBar>,
+    mod_: Mod,
+    dependency: bool,
+    reason: String,
+    pad_len: usize,
+) -> Outcome {
+    progress_bar
+        .lock()
+        .println(skip_line(&mod_, &reason, pad_len));
+
+    Outcome::Skipped(SkippedMod {
+        name: mod_.name,
+        identifier: mod_.identifier,
+        dependency,
+        reason,
+    })
+}
+
+fn skip_line(mod_: &Mod, reason: &str, pad_len: usize) -> String {
+    format!(
+        "{} {:pad_len$}  {}",
+        "-".yellow(),
+        mod_.name,
+        format!("skipped, {reason}").dimmed()
+    )
+}
+
+/// Build a lockfile for `resolution`, which was resolved for `ferium`
+///
+/// Entries of `previous` are reused for files that did not change,
+/// so only new files have their metadata fetched from the platforms
+pub async fn build_lockfile(
+    resolution: &Resolution,
+    previous: Option<&Lockfile>,
+    ferium: &FeriumConfig,
+) -> Result<Lockfile> {
+    let mut tasks = JoinSet::new();
+
+    for resolved in resolution.resolved.iter().cloned() {
+        let url = resolved.download.download_url.to_string();
+        let previous = previous.and_then(|lock| lock.find_by_url(&url)).cloned();
+
+        tasks.spawn(async move {
+            // Entries locked before version numbers were recorded are fetched again once
+            let (version_id, version, hash) = match previous {
+                Some(previous) if previous.version.is_some() => {
+                    (previous.version_id, previous.version, previous.hash)
+                }
+                _ => {
+                    let metadata =
+                        fetch_metadata(&resolved.mod_.identifier, &resolved.download).await?;
+                    (metadata.version_id, metadata.version, metadata.hash)
+                }
+            };
+
+            Ok::<_, UpgradeError>(LockedMod {
+                name: resolved.mod_.name,
+                platform: Platform::from(&resolved.mod_.identifier),
+                identifier: resolved.mod_.identifier,
+                dependency: resolved.dependency,
+                version_id,
+                version,
+                filename: resolved.download.filename(),
+                url,
+                size: resolved.download.length,
+                hash,
+                dependencies: resolved.dependencies,
+            })
+        });
+    }
+
+    let mods = tasks
+        .join_all()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Lockfile::new(
+        ResolvedFor::from(ferium),
+        mods,
+        resolution.skipped.clone(),
+    ))
+}
+
+/// Resolve `profile` with the settings in `config`, update its lockfile and install the files
+pub async fn upgrade(profile: &Profile, user: bool, config: &FerriteConfig) -> Result<()> {
+    let resolution = get_platform_downloadables(profile, user, &config.ferium).await?;
+
+    let lock_path = config.lock_path();
+    let previous = load_lock(&lock_path)?;
+    let lockfile = build_lockfile(&resolution, previous.as_ref(), &config.ferium).await?;
+    let error = resolution.error;
+    if error {
+        // A partial lockfile would silently drop the mods that failed to resolve
+        println!(
+            "{}",
+            format!(
+                "Not updating {} because some mods could not be resolved",
+                lock_path.display()
+            )
+            .yellow()
+        );
+    } else if previous.as_ref() != Some(&lockfile) {
+        lockfile.write(&lock_path)?;
+        if user {
+            println!("{}", format!("Updated {}", lock_path.display()).dimmed());
+        }
+    }
+
+    install(profile, lockfile.mods, error).await
+}
+
+/// Download exactly the files recorded in `lockfile`, without resolving any versions
+pub async fn install_locked(profile: &Profile, lockfile: &Lockfile) -> Result<()> {
+    install(profile, lockfile.mods.clone(), false).await
+}
+
+/// Install `to_download` and the user's own jars to the profile's output directory,
+/// moving anything else that is there to `.old`
+async fn install(profile: &Profile, mut to_download: Vec<LockedMod>, error: bool) -> Result<()> {
     let mut to_install = Vec::new();
     if profile.output_dir.join("user").exists()
         && profile.filters.mod_loader() != Some(&ModLoader::Quilt)
@@ -206,11 +490,6 @@ pub async fn upgrade(
     }
 
     clean(&profile.output_dir, &mut to_download, &mut to_install).await?;
-    to_download
-        .iter_mut()
-        // Download directly to the output directory
-        .map(|thing| thing.output = thing.filename().into())
-        .for_each(drop); // Doesn't drop any data, just runs the iterator
     if to_download.is_empty() && to_install.is_empty() {
         println!("\n{}", "All up to date!".bold());
     } else {
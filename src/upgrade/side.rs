@@ -0,0 +1,79 @@
+use super::metadata::curseforge_file_id;
+use colored::Colorize as _;
+use ferinth::structures::project::ProjectSupportRange;
+use libium::{CURSEFORGE_API, MODRINTH_API, config::structs::ModIdentifier, upgrade::DownloadData};
+use std::fmt::Display;
+
+/// Why each of `files` is not needed on a server, if its platform says it only runs on clients
+///
+/// Every platform is asked once, for all of its files. Files whose platform does not say, or
+/// could not be asked, are assumed to be needed.
+pub async fn client_only(files: &[(&ModIdentifier, &DownloadData)]) -> Vec<Option<String>> {
+    let mut reasons = vec![None; files.len()];
+
+    let modrinth = files
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (identifier, _))| match identifier {
+            ModIdentifier::ModrinthProject(id, _) => Some((i, id.as_str())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !modrinth.is_empty() {
+        let ids = modrinth.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+        match MODRINTH_API.project_get_multiple(&ids).await {
+            Ok(projects) => {
+                for (i, id) in modrinth {
+                    if projects.iter().any(|project| {
+                        (project.id == id || project.slug == id)
+                            && project.server_side == ProjectSupportRange::Unsupported
+                    }) {
+                        reasons[i] = Some("Modrinth marks it unsupported on servers".to_string());
+                    }
+                }
+            }
+            Err(err) => lookup_failed("Modrinth", err),
+        }
+    }
+
+    let curseforge = files
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (identifier, download))| match identifier {
+            ModIdentifier::CurseForgeProject(..) => Some((i, curseforge_file_id(download)?)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !curseforge.is_empty() {
+        let ids = curseforge.iter().map(|(_, id)| *id).collect();
+        match CURSEFORGE_API.get_files(ids).await {
+            // The files are returned in the order they were asked for
+            Ok(found) => {
+                for ((i, _), file) in curseforge.into_iter().zip(found) {
+                    let Some(file) = file else {
+                        continue;
+                    };
+                    // CurseForge lists the environments a file runs in among its game versions
+                    let lists =
+                        |environment: &str| file.game_versions.iter().any(|v| v == environment);
+                    if lists("Client") && !lists("Server") {
+                        reasons[i] = Some("CurseForge only lists the file for clients".to_string());
+                    }
+                }
+            }
+            Err(err) => lookup_failed("CurseForge", err),
+        }
+    }
+
+    reasons
+}
+
+fn lookup_failed(platform: &str, err: impl Display) {
+    println!(
+        "{}",
+        format!(
+            "Warning: could not ask {platform} which mods only run on clients, installing them anyway: {err}"
+        )
+        .yellow()
+    );
+}
@@ -0,0 +1,19 @@
+use super::error::Result;
+use crate::lock::{FileHash, HashAlgorithm};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest as _, Sha512};
+use std::{fs, path::Path};
+
+/// Check whether the file at `path` matches the `expected` hash
+pub fn verify(path: &Path, expected: &FileHash) -> Result<bool> {
+    let bytes = fs::read(path)?;
+
+    let actual = match expected.algorithm {
+        HashAlgorithm::Sha512 => format!("{:x}", Sha512::digest(&bytes)),
+        HashAlgorithm::Sha1 => format!("{:x}", Sha1::digest(&bytes)),
+        HashAlgorithm::Md5 => format!("{:x}", Md5::digest(&bytes)),
+    };
+
+    Ok(actual.eq_ignore_ascii_case(&expected.value))
+}
@@ -0,0 +1,134 @@
+use crate::config::FerriteConfig;
+use crate::lock::Lockfile;
+use crate::tree::{Edge, Graph, Node};
+use colored::Colorize as _;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WhyError {
+    #[error("'{0}' is neither a locked mod nor a jar in {1}")]
+    NotFound(String, String),
+}
+
+pub type Result<T> = std::result::Result<T, WhyError>;
+
+/// Explain why the jar or mod `query` is installed: which mods in the config require it and
+/// through which dependencies, or that it comes from `user/` or is not managed by ferrite
+pub fn why(config: &FerriteConfig, lockfile: &Lockfile, query: &str) -> Result<()> {
+    let output_dir = config.output_dir();
+    // Accept paths like mods/sodium.jar as well as file names
+    let filename = Path::new(query)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| query.to_string());
+
+    let graph = Graph::new(lockfile);
+    let node = graph.nodes.iter().find(|node| {
+        node.filename == filename
+            || format!("{}.disabled", node.filename) == filename
+            || node.project().eq_ignore_ascii_case(query)
+            || node.name.eq_ignore_ascii_case(query)
+    });
+
+    if output_dir.join("user").join(&filename).is_file() {
+        println!(
+            "{} comes from {}, it is copied into {} on every install",
+            filename.bold(),
+            "user/".cyan(),
+            config.output_path
+        );
+        return Ok(());
+    }
+
+    let Some(node) = node else {
+        if let Some(skipped) = lockfile.skipped.iter().find(|skipped| {
+            skipped
+                .identifier
+                .display_name()
+                .eq_ignore_ascii_case(query)
+                || skipped.name.eq_ignore_ascii_case(query)
+        }) {
+            println!(
+                "{} is not installed, it was skipped: {}",
+                skipped.name.bold(),
+                skipped.reason
+            );
+            return Ok(());
+        }
+        if output_dir.join(&filename).is_file() {
+            println!(
+                "{} is not managed by ferrite, it is neither in ferrite.lock nor in user/",
+                filename.bold()
+            );
+            return Ok(());
+        }
+        return Err(WhyError::NotFound(
+            query.to_string(),
+            config.output_path.clone(),
+        ));
+    };
+
+    println!(
+        "{} is {} {}",
+        node.filename.bold(),
+        node.name.bold(),
+        format!("({})", node.id).dimmed()
+    );
+    if !node.dependency {
+        println!("It is in {}", "ferium.mods".cyan());
+    }
+
+    let chains = graph.chains(&node.id);
+    if chains.is_empty() {
+        if node.dependency {
+            println!(
+                "{}",
+                "No mod that requires it is recorded, run `ferrite upgrade` to record them"
+                    .yellow()
+            );
+        }
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        if node.dependency {
+            "Required by"
+        } else {
+            "Also required by"
+        }
+    );
+    for chain in chains {
+        println!("  {}", show_chain(&graph, node, &chain));
+    }
+
+    Ok(())
+}
+
+/// A chain as `Mod → Dependency → ... → node`, noting where an override replaced a dependency
+fn show_chain(graph: &Graph, node: &Node, chain: &[&Edge]) -> String {
+    let name = |id: &str| {
+        graph
+            .node(id)
+            .map_or_else(|| id.to_string(), |node| node.name.clone())
+    };
+
+    let mut shown = chain
+        .first()
+        .map(|edge| name(&edge.from).bold().to_string())
+        .unwrap_or_default();
+    for edge in chain {
+        let to = if edge.to == node.id {
+            name(&edge.to).bold().to_string()
+        } else {
+            name(&edge.to)
+        };
+        shown.push_str(&format!(" → {to}"));
+        if let Some(replaces) = &edge.replaces {
+            shown.push_str(&format!(" {}", format!("(override for {replaces})").cyan()));
+        }
+    }
+
+    shown
+}